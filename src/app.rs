@@ -1,11 +1,18 @@
-use std::any::{type_name, TypeId};
+use std::{
+    any::{type_name, TypeId},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex, RwLock,
+    },
+};
 
 use crate::{
-    app_builder::AppBuilder, type_names::TypeNames, AppWorkload, AppWorkloadInfo, Plugin,
-    TypeIdBuckets,
+    app_builder::AppBuilder, commands::apply_commands, type_names::TypeNames, AppExit, AppHandle,
+    AppHandleChannel, AppRunner, AppWorkload, AppWorkloadInfo, BuildError, CommandQueue, Plugin,
+    PluginToggles, TypeIdBuckets,
 };
 use shipyard::*;
-use tracing::trace_span;
+use tracing::{trace_span, warn};
 
 #[allow(clippy::needless_doctest_main)]
 /// Containers of app logic and data
@@ -13,6 +20,12 @@ pub struct App {
     pub world: World,
     pub(crate) type_names: TypeNames,
     workload_ids: TypeIdBuckets<()>,
+    /// What [App::update] runs
+    default_workload: RwLock<DefaultWorkload>,
+    /// Drives [App::update] from [App::run_loop], taken out while the loop runs
+    runner: Mutex<Option<AppRunner>>,
+    /// Set once a [TimePlugin](crate::TimePlugin) updates [Time](crate::Time), so later ones don't advance it again
+    time_updated: AtomicBool,
 }
 
 impl App {
@@ -26,6 +39,9 @@ impl App {
             world,
             workload_ids: TypeIdBuckets::new("Count times workload plugin added", &type_names),
             type_names,
+            default_workload: RwLock::new(DefaultWorkload::Unset),
+            runner: Mutex::new(Some(AppRunner::default())),
+            time_updated: AtomicBool::new(false),
         }
     }
 
@@ -42,25 +58,195 @@ impl App {
     where
         P: Plugin + 'static,
     {
+        let span = trace_span!("add_plugin_workload_with_info", plugin = ?type_name::<P>());
+        let _span = span.enter();
+        let (name, workload_type_id) = self.next_plugin_workload_name::<P>();
+        let mut builder = AppBuilder::new(&self);
+        builder.add_plugin(plugin);
+        builder.finish_with_info_named(name, workload_type_id)
+    }
+
+    /// Fallible version of [App::add_plugin_workload] which reports every [BuildError] instead of panicking.
+    ///
+    /// See [App::try_add_plugin_workload_with_info] for what an `Err` leaves behind.
+    #[track_caller]
+    pub fn try_add_plugin_workload<P>(&mut self, plugin: P) -> Result<AppWorkload, Vec<BuildError>>
+    where
+        P: Plugin + 'static,
+    {
+        self.try_add_plugin_workload_with_info(plugin)
+            .map(|(workload, _)| workload)
+    }
+
+    /// Fallible version of [App::add_plugin_workload_with_info] which reports every [BuildError] instead of panicking.
+    ///
+    /// On `Err` the workload's name is released, so adding the plugin again names the workload the same way.
+    /// But whatever the plugins already applied to the [App] while building stays: uniques, the [AppRunner],
+    /// the fixed timestep and plugin toggles. Prefer fixing the errors and building a new [App] over retrying.
+    #[track_caller]
+    pub fn try_add_plugin_workload_with_info<P>(
+        &mut self,
+        plugin: P,
+    ) -> Result<(AppWorkload, AppWorkloadInfo), Vec<BuildError>>
+    where
+        P: Plugin + 'static,
+    {
+        let span = trace_span!("try_add_plugin_workload_with_info", plugin = ?type_name::<P>());
+        let _span = span.enter();
+        let (name, workload_type_id) = self.next_plugin_workload_name::<P>();
+        let mut builder = AppBuilder::new(&self);
+        builder.add_plugin(plugin);
+        let finished = builder.try_finish_with_info_named(name, workload_type_id);
+        if finished.is_err() {
+            self.release_plugin_workload_name::<P>();
+        }
+        finished
+    }
+
+    fn next_plugin_workload_name<P: 'static>(
+        &mut self,
+    ) -> (std::borrow::Cow<'static, str>, TypeId) {
         let workload_name = type_name::<P>();
         let workload_type_id = TypeId::of::<P>();
-        let span = trace_span!("add_plugin_workload_with_info", plugin = ?workload_name);
-        let _span = span.enter();
         let name: std::borrow::Cow<'static, str> = match self.workload_ids.associate_type::<P>(()) {
             crate::AssociateResult { nth } if nth == 1 => workload_name.into(),
             crate::AssociateResult { nth } => format!("{}_{}", workload_name, nth).into(),
         };
-        let mut builder = AppBuilder::new(&self);
-        plugin.build(&mut builder);
-        builder.finish_with_info_named(name, workload_type_id)
+        (name, workload_type_id)
+    }
+
+    /// Undo the last `next_plugin_workload_name` of `P`, whose workload failed to build
+    fn release_plugin_workload_name<P: 'static>(&mut self) {
+        if let Some(names) = self
+            .workload_ids
+            .type_plugins_lookup
+            .get_mut(&TypeId::of::<P>())
+        {
+            names.pop();
+        }
     }
 
-    /// Runs default workload
+    /// Runs the default [AppWorkload], which is the first workload finished for this app with each of its stages.
+    ///
+    /// **Breaking change:** this used to always call [World::run_default]. Since each stage is its own shipyard
+    /// workload, a default set with [World::set_default_workload] is now only run if no [AppWorkload] was finished yet.
+    /// Use [App::set_default_workload] to pick another [AppWorkload], or [App::use_world_default_workload] to go back
+    /// to [World::run_default].
+    /// Either way, [Commands](crate::Commands) are applied after each shipyard workload.
     #[track_caller]
     pub fn update(&self) {
         let span = trace_span!("update");
         let _span = span.enter();
-        self.world.run_default().unwrap();
+        let default_workload = match &*self.default_workload.read().unwrap() {
+            DefaultWorkload::App(workload) => Some(workload.clone()),
+            DefaultWorkload::Unset | DefaultWorkload::World => None,
+        };
+        match default_workload {
+            Some(workload) => workload.run(self),
+            None => {
+                self.world.run_default().unwrap();
+                apply_commands(&self.world);
+            }
+        }
+    }
+
+    /// Replace the [AppWorkload] run by [App::update]
+    pub fn set_default_workload(&self, workload: &AppWorkload) {
+        *self.default_workload.write().unwrap() = DefaultWorkload::App(workload.clone());
+    }
+
+    /// Make [App::update] call [World::run_default], even after [AppWorkload]s are finished
+    pub fn use_world_default_workload(&self) {
+        *self.default_workload.write().unwrap() = DefaultWorkload::World;
+    }
+
+    /// A cloneable [Send] handle to queue work for this app from other threads.
+    ///
+    /// The work is applied with the commands of each update by the [AppHandlePlugin](crate::AppHandlePlugin), which must be added to a workload.
+    pub fn handle(&self) -> AppHandle {
+        if let Ok(channel) = self.world.borrow::<UniqueView<AppHandleChannel>>() {
+            return channel.handle();
+        }
+
+        warn!("App::handle called before AppHandlePlugin was added, work is queued until it is.");
+        let channel = AppHandleChannel::default();
+        let handle = channel.handle();
+        self.world.add_unique(channel).unwrap();
+        handle
+    }
+
+    /// Replace the [AppRunner] used by [App::run_loop], also available through [AppBuilder::set_runner].
+    ///
+    /// If a loop is running, it keeps its runner and the new one is used by the next [App::run_loop].
+    pub fn set_runner(&self, runner: AppRunner) {
+        self.add_app_exit();
+        *self.runner.lock().unwrap() = Some(runner);
+    }
+
+    /// Run the default workload with the [AppRunner] until it finishes or [AppExit] is requested.
+    ///
+    /// Clears any previous [AppExit] request first. Returns the number of updates run.
+    #[track_caller]
+    pub fn run_loop(&self) -> usize {
+        let span = trace_span!("run_loop");
+        let _span = span.enter();
+        self.add_app_exit();
+        *self.world.borrow::<UniqueViewMut<AppExit>>().unwrap() = AppExit::default();
+
+        // the runner isn't locked while it runs, so it can be replaced from other threads (or systems)
+        let runner = self.runner.lock().unwrap().take().unwrap_or_else(|| {
+            warn!("App::run_loop called while another loop is running, updating once.");
+            AppRunner::default()
+        });
+        let updates = runner.run(self);
+
+        let mut next_runner = self.runner.lock().unwrap();
+        if next_runner.is_none() {
+            *next_runner = Some(runner);
+        }
+        updates
+    }
+
+    /// Enable or disable every system added by plugin `P`, which must return `true` from [Plugin::can_be_disabled].
+    ///
+    /// Takes effect on the next run of the workloads containing the plugin. Returns `false` if `P` can not be toggled.
+    pub fn set_plugin_enabled<P: Plugin>(&self, enabled: bool) -> bool {
+        match self.world.borrow::<UniqueViewMut<PluginToggles>>() {
+            Ok(mut toggles) => toggles.set_enabled::<P>(enabled),
+            Err(_) => {
+                warn!(
+                    "Plugin({}) cannot be toggled, no plugins which can be disabled were added.",
+                    type_name::<P>()
+                );
+                false
+            }
+        }
+    }
+
+    /// Add the [AppExit] unique the first time a runner is used, so systems can request to exit
+    fn add_app_exit(&self) {
+        if self.world.borrow::<UniqueView<AppExit>>().is_err() {
+            self.world.add_unique(AppExit::default()).unwrap();
+        }
+    }
+
+    /// Add the [CommandQueue] unique the first time an [AppWorkload] is finished, so its systems can record [Commands](crate::Commands)
+    pub(crate) fn add_command_queue(&self) {
+        if self.world.borrow::<UniqueView<CommandQueue>>().is_err() {
+            self.world.add_unique(CommandQueue::default()).unwrap();
+        }
+    }
+
+    /// Returns `true` for the first caller only, which is the one to update [Time](crate::Time)
+    pub(crate) fn claim_time_updates(&self) -> bool {
+        !self.time_updated.swap(true, Ordering::SeqCst)
+    }
+
+    pub(crate) fn set_default_workload_if_unset(&self, workload: &AppWorkload) {
+        let mut default_workload = self.default_workload.write().unwrap();
+        if let DefaultWorkload::Unset = *default_workload {
+            *default_workload = DefaultWorkload::App(workload.clone());
+        }
     }
 
     #[track_caller]
@@ -68,6 +254,14 @@ impl App {
         self.world.run(s).unwrap()
     }
 
+    /// Run a single system, then apply every queued [Commands](crate::Commands), including the ones it recorded
+    #[track_caller]
+    pub fn run_and_apply<'s, B, R, S: shipyard::System<'s, (), B, R>>(&'s self, s: S) -> R {
+        let result = self.world.run(s).unwrap();
+        apply_commands(&self.world);
+        result
+    }
+
     #[track_caller]
     pub fn run_with_data<'s, Data, B, R, S: shipyard::System<'s, (Data,), B, R>>(
         &'s self,
@@ -77,3 +271,12 @@ impl App {
         self.world.run_with_data(s, data).unwrap()
     }
 }
+
+/// What [App::update] runs
+enum DefaultWorkload {
+    /// [World::run_default], until the first [AppWorkload] is finished
+    Unset,
+    App(AppWorkload),
+    /// [World::run_default], chosen with [App::use_world_default_workload]
+    World,
+}
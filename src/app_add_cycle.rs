@@ -1,7 +1,15 @@
-use std::{any::TypeId, borrow::Cow, collections::HashSet, sync::Arc};
+use std::{
+    any::TypeId,
+    borrow::Cow,
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+
+use shipyard::info;
 
 use crate::{
-    App, AppWorkload, AppWorkloadInfo, PluginAssociated, TypeIdBuckets, WorkloadSignature,
+    graph_export::WorkloadGraph, App, AppWorkload, AppWorkloadInfo, PluginAssociated, PluginGraph,
+    SystemProvenance, TypeIdBuckets, WorkloadSignature,
 };
 
 /// Associations made by this workload which includes the list of plugins and their reasons associated.
@@ -35,6 +43,10 @@ pub enum CycleCheckError {
         tracked_unique: &'static str,
         conflicts: Vec<CycleWorkloadAssociations>,
     },
+    EventSwapInMultipleWorkloads {
+        events: &'static str,
+        conflicts: Vec<CycleWorkloadAssociations>,
+    },
 }
 
 pub struct CycleSummary {
@@ -45,6 +57,9 @@ pub struct CycleSummary {
 pub struct CycleWorkloadSummary {
     name: Cow<'static, str>,
     signature: Arc<WorkloadSignature>,
+    plugin_graph: PluginGraph,
+    batch_info: Vec<info::BatchInfo>,
+    systems: Vec<SystemProvenance>,
 }
 
 impl std::fmt::Debug for CycleSummary {
@@ -56,6 +71,21 @@ impl std::fmt::Debug for CycleSummary {
     }
 }
 
+impl CycleSummary {
+    pub(crate) fn workload_graphs(&self) -> Vec<WorkloadGraph> {
+        self.workload_info
+            .iter()
+            .map(|workload| WorkloadGraph {
+                name: &workload.name,
+                signature: &workload.signature,
+                plugin_graph: &workload.plugin_graph,
+                batch_info: &workload.batch_info,
+                systems: &workload.systems,
+            })
+            .collect()
+    }
+}
+
 impl std::fmt::Debug for CycleWorkloadSummary {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct(&self.name)
@@ -70,6 +100,8 @@ impl App {
     ///
     /// Conflicts guarded against:
     ///  * Two different workloads require update_pack for the same storage
+    ///  * Two different workloads track the same tracked unique
+    ///  * Two different workloads add (and swap) the same events
     pub fn add_cycle(
         &mut self,
         cycle: Vec<(AppWorkload, AppWorkloadInfo)>,
@@ -77,10 +109,20 @@ impl App {
         // to track the plugins added so far (so we can avoid them accidentally conflicting with themselves)
         let mut workload_plugins_added = HashSet::new();
         let mut names_checked = Vec::new();
+        let mut conditions = HashMap::new();
+        let mut fixed_names = HashSet::new();
+        let mut cumulative_update_packed = TypeIdBuckets::<CycleWorkloadAssociations>::new(
+            "update packed storages in workloads",
+            &self.type_names,
+        );
         let mut cumulative_tracked_uniques = TypeIdBuckets::<CycleWorkloadAssociations>::new(
             "tracked uniques in workloads",
             &self.type_names,
         );
+        let mut cumulative_events = TypeIdBuckets::<CycleWorkloadAssociations>::new(
+            "events swapped in workloads",
+            &self.type_names,
+        );
 
         let mut summary = CycleSummary {
             cycle_order: Vec::new(),
@@ -88,12 +130,15 @@ impl App {
         };
 
         for (
-            _workloads,
+            workload,
             AppWorkloadInfo {
                 name,
                 plugin_id,
+                plugin_graph,
                 signature,
-                batch_info: _,
+                batch_info,
+                systems,
+                stages: _,
                 type_names: _,
             },
         ) in cycle
@@ -115,8 +160,26 @@ impl App {
                 summary.workload_info.push(CycleWorkloadSummary {
                     name: name.clone(),
                     signature: signature.clone(),
+                    plugin_graph,
+                    batch_info,
+                    systems,
                 });
 
+                // account for update packed storages
+                for ((update_packed_type, _), assoc) in signature.track_update_packed.entries() {
+                    if !assoc.is_empty() {
+                        // We need to account for these update packs
+                        cumulative_update_packed.associate(
+                            update_packed_type,
+                            CycleWorkloadAssociations {
+                                plugins: assoc,
+                                workload: name.clone(),
+                                workload_plugin_id: plugin_id,
+                            },
+                        );
+                    }
+                }
+
                 // account for tracked uniques
                 for ((tracked_type, _), assoc) in signature.track_tracked_uniques.entries() {
                     if !assoc.is_empty() {
@@ -131,13 +194,45 @@ impl App {
                         );
                     }
                 }
+
+                // account for events
+                for ((events_type, _), assoc) in signature.track_events.entries() {
+                    if !assoc.is_empty() {
+                        // Each workload adding the events swaps its buffers
+                        cumulative_events.associate(
+                            events_type,
+                            CycleWorkloadAssociations {
+                                plugins: assoc,
+                                workload: name.clone(),
+                                workload_plugin_id: plugin_id,
+                            },
+                        );
+                    }
+                }
             }
 
-            names_checked.push(name.clone());
+            // each stage of the workload runs in order
+            names_checked.extend(workload.names);
+            conditions.extend(workload.conditions);
+            fixed_names.extend(workload.fixed_names);
         }
 
         let mut errs = Vec::<CycleCheckError>::new();
 
+        // update pack
+        errs.extend(
+            cumulative_update_packed
+                .entries()
+                .into_iter()
+                .filter(|((_, _), workloads_dependent)| workloads_dependent.len() > 1)
+                .map(|((_, update_pack_storage_name), workloads_dependent)| {
+                    CycleCheckError::UpdatePackResetInMultipleWorkloads {
+                        update_pack: update_pack_storage_name,
+                        conflicts: workloads_dependent,
+                    }
+                }),
+        );
+
         // tracked unique
         errs.extend(
             cumulative_tracked_uniques
@@ -152,6 +247,20 @@ impl App {
                 }),
         );
 
+        // events
+        errs.extend(
+            cumulative_events
+                .entries()
+                .into_iter()
+                .filter(|((_, _), workloads_dependent)| workloads_dependent.len() > 1)
+                .map(|((_, events_storage_name), workloads_dependent)| {
+                    CycleCheckError::EventSwapInMultipleWorkloads {
+                        events: events_storage_name,
+                        conflicts: workloads_dependent,
+                    }
+                }),
+        );
+
         if !errs.is_empty() {
             return Err(errs);
         }
@@ -159,6 +268,8 @@ impl App {
         Ok((
             AppWorkload {
                 names: names_checked,
+                conditions,
+                fixed_names,
             },
             summary,
         ))
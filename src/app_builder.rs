@@ -1,18 +1,39 @@
 use crate::{
-    app::App, plugin::Plugin, tracked_unique::reset_tracked_unique, type_names::TypeNames,
+    app::App,
+    commands::apply_commands,
+    events::{swap_events, Events},
+    plugin::Plugin,
+    plugin_toggles::PluginToggles,
+    run_condition::{ConditionalSystems, WorkloadCondition},
+    runner::AppRunner,
+    stage,
+    time::{expend_fixed_timestep, FixedTimestep, Time},
+    tracked_unique::reset_tracked_unique,
+    type_names::TypeNames,
+    update_pack::reset_update_pack,
 };
 use shipyard::*;
 use std::{
     any::{type_name, TypeId},
     borrow::Cow,
     collections::hash_map::Entry,
-    collections::HashMap,
+    collections::{HashMap, HashSet},
+    panic::Location,
     sync::Arc,
 };
 use tracing::*;
 
+mod build_error;
+mod plugin_graph;
 mod plugin_id;
-use plugin_id::PluginId;
+mod system_order;
+mod system_provenance;
+pub use build_error::BuildError;
+pub use plugin_graph::{PluginEdge, PluginEdgeKind, PluginGraph, PluginNode};
+pub use plugin_id::PluginId;
+use system_order::{order_stage_systems, PendingSystem, SystemOrdering};
+pub use system_order::{AppSystem, SystemConfig};
+pub use system_provenance::{SystemKind, SystemProvenance};
 
 /// Used when a workload is created without a plugin
 pub static DEFAULT_WORKLOAD_NAME: &str = "update";
@@ -38,6 +59,31 @@ impl std::fmt::Debug for PluginAssociated {
     }
 }
 
+impl std::fmt::Display for PluginAssociated {
+    fn fmt(&self, mut f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(&mut f, "{}: {}", &self.plugin, &self.reason)
+    }
+}
+
+/// A unique declared with [AppBuilder::depends_on_unique] that was neither provided by a plugin
+/// in the workload nor already present in the [World].
+#[derive(Clone, Debug)]
+pub struct UnmetUniqueDependency {
+    pub unique: &'static str,
+    /// Plugins which declared the dependency and their reasons
+    pub dependents: Vec<PluginAssociated>,
+}
+
+impl std::fmt::Display for UnmetUniqueDependency {
+    fn fmt(&self, mut f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(&mut f, "Unique({}) is required by", self.unique)?;
+        for dependent in self.dependents.iter() {
+            write!(&mut f, "\n  - {}", dependent)?;
+        }
+        Ok(())
+    }
+}
+
 pub(crate) type PluginsAssociatedMap = TypeIdBuckets<PluginAssociated>;
 
 pub(crate) struct TypeIdBuckets<T> {
@@ -136,7 +182,7 @@ impl<T: Clone + std::fmt::Debug> TypeIdBuckets<T> {
 
 impl TypeIdBuckets<PluginAssociated> {
     /// Return new number of plugins associated
-    pub(crate) fn associate_plugin<Type: Component>(
+    pub(crate) fn associate_plugin<Type: 'static>(
         &mut self,
         plugin: &PluginId,
         reason: &'static str,
@@ -153,8 +199,11 @@ impl TypeIdBuckets<PluginAssociated> {
 #[derive(Debug)]
 pub(crate) struct WorkloadSignature {
     /// track the plugins directly required by other plugins
-    #[allow(unused)]
     pub track_plugin_dependencies: PluginsAssociatedMap,
+    /// track the plugins required by other plugins, which add their default when missing
+    pub track_plugin_default_dependencies: PluginsAssociatedMap,
+    /// plugin type id to list of plugins which added it
+    pub track_plugins_added: PluginsAssociatedMap,
     /// unique type id to list of plugin type ids that provided a value for it it
     pub track_uniques_provided: PluginsAssociatedMap,
     pub track_tracked_uniques_provided: PluginsAssociatedMap,
@@ -164,6 +213,8 @@ pub(crate) struct WorkloadSignature {
     pub track_update_packed: PluginsAssociatedMap,
     /// tracked uniques storage type id to list of (plugin type id, reason string)
     pub track_tracked_uniques: PluginsAssociatedMap,
+    /// events storage type id to list of plugins which added the event, the workload swaps its buffers
+    pub track_events: PluginsAssociatedMap,
 }
 
 impl WorkloadSignature {
@@ -173,6 +224,11 @@ impl WorkloadSignature {
                 "Plugin depends on Plugin",
                 &type_names,
             ),
+            track_plugin_default_dependencies: PluginsAssociatedMap::new(
+                "Plugin depends on default Plugin",
+                &type_names,
+            ),
+            track_plugins_added: PluginsAssociatedMap::new("Plugin adds Plugin", &type_names),
             track_uniques_provided: PluginsAssociatedMap::new(
                 "Plugin provides Unique",
                 &type_names,
@@ -193,6 +249,7 @@ impl WorkloadSignature {
                 "Plugin requires tracked unique",
                 &type_names,
             ),
+            track_events: PluginsAssociatedMap::new("Plugin adds Events", &type_names),
         }
     }
 }
@@ -200,15 +257,26 @@ impl WorkloadSignature {
 /// Configure [App]s using the builder pattern
 pub struct AppBuilder<'a> {
     pub app: &'a App,
-    resets: Vec<WorkloadSystem>,
-    systems: Vec<WorkloadSystem>,
+    resets: Vec<(PendingSystem, SystemProvenance)>,
+    systems: Vec<(PendingSystem, SystemProvenance, SystemOrdering)>,
+    /// plugins which returned true from [Plugin::can_be_disabled]
+    track_toggleable_plugins: HashMap<TypeId, &'static str>,
+    /// stages in the order they run
+    stages: Vec<&'static str>,
     /// track the plugins previously added to enable checking that plugin peer dependencies are satisified
     track_added_plugins: HashMap<TypeId, PluginId>,
+    /// plugins to add by default if nothing else added them by the time we finish
+    pending_default_plugins: Vec<(TypeId, fn(&mut AppBuilder))>,
     /// track the currently being used plugin ([PluginId] is a stack since some plugins add other plugins creating a nest)
-    // TODO: Track "Plugin"s for each thing
     track_current_plugin: PluginId,
     /// take a record of type names as we come across them for diagnostics
     track_type_names: TypeNames,
+    /// update packed storages which already have a reset system scheduled
+    track_update_pack_resets: HashSet<TypeId>,
+    /// how to check if a unique depended on is already in the [World]
+    unique_presence_checks: HashMap<TypeId, fn(&World) -> bool>,
+    /// problems found while building, reported upon finishing
+    errors: Vec<BuildError>,
     signature: WorkloadSignature,
 }
 
@@ -221,22 +289,44 @@ impl<'a> AppBuilder<'a> {
 #[derive(Clone, Debug)]
 pub struct AppWorkload {
     pub(crate) names: Vec<std::borrow::Cow<'static, str>>,
+    /// Workloads which only run if their condition is met and their plugins are enabled
+    pub(crate) conditions: HashMap<std::borrow::Cow<'static, str>, WorkloadCondition>,
+    /// Workloads of the [stage::FIXED_UPDATE] stage, which run once per fixed step
+    pub(crate) fixed_names: HashSet<std::borrow::Cow<'static, str>>,
 }
 
 #[derive(Clone, Debug)]
 pub struct AppWorkloadInfo {
     #[allow(unused)]
     pub(crate) type_names: Blind<TypeNames>,
-    #[allow(unused)]
     pub(crate) batch_info: Vec<info::BatchInfo>,
+    /// Each stage which was added to the world as its own workload, in the order they run
+    pub(crate) stages: Vec<StageInfo>,
+    /// Where each system (including reset systems) came from, in the order added
+    pub(crate) systems: Vec<SystemProvenance>,
     /// Self-imposed constraints declared by the workload
     pub(crate) signature: Arc<WorkloadSignature>,
     /// Derived from this plugin
     pub(crate) plugin_id: TypeId,
+    /// Plugins added to the workload and their relationships
+    pub(crate) plugin_graph: PluginGraph,
     /// Workload name assigned in the world
     pub name: Cow<'static, str>,
 }
 
+/// A stage of an [AppWorkload], which is added to the [World] as its own workload
+#[derive(Clone, Debug)]
+pub struct StageInfo {
+    pub stage: &'static str,
+    /// Name of the stage's first workload in the world
+    pub name: Cow<'static, str>,
+    /// Every workload of the stage in the world, systems of plugins which can be disabled and systems ordered
+    /// after others are split into their own workloads.
+    /// Variants skipping conditional systems are added to the world when first needed and not listed.
+    pub workloads: Vec<Cow<'static, str>>,
+    pub batch_info: Vec<info::BatchInfo>,
+}
+
 #[derive(Clone)]
 pub(crate) struct Blind<T: Clone + 'static>(pub T);
 
@@ -246,18 +336,103 @@ impl<T: Clone + 'static> std::fmt::Debug for Blind<T> {
     }
 }
 
+impl AppWorkloadInfo {
+    /// Plugins added to the workload, with who added them and who depends on them
+    pub fn plugin_graph(&self) -> &PluginGraph {
+        &self.plugin_graph
+    }
+
+    /// How shipyard batched the systems of this workload (across all stages)
+    pub fn batch_info(&self) -> &[info::BatchInfo] {
+        &self.batch_info
+    }
+
+    /// Each stage which had systems, in the order they run
+    pub fn stages(&self) -> &[StageInfo] {
+        &self.stages
+    }
+
+    /// Every system (including reset systems) with the plugins which added it, in the order added
+    pub fn systems(&self) -> &[SystemProvenance] {
+        &self.systems
+    }
+
+    /// Each system of each batch, alongside the plugins which added it
+    pub fn batches_with_provenance(
+        &self,
+    ) -> Vec<Vec<(&info::SystemInfo, Option<&SystemProvenance>)>> {
+        // the same system may be added more than once, so attribute each provenance only once
+        let mut attributed = vec![false; self.systems.len()];
+        self.batch_info
+            .iter()
+            .map(|batch| {
+                batch
+                    .systems
+                    .iter()
+                    .map(|system_info| {
+                        let provenance = self
+                            .systems
+                            .iter()
+                            .enumerate()
+                            .find(|(index, provenance)| {
+                                !attributed[*index] && provenance.system == system_info.name
+                            })
+                            .map(|(index, provenance)| {
+                                attributed[index] = true;
+                                provenance
+                            });
+                        (system_info, provenance)
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+}
+
 impl AppWorkload {
     #[track_caller]
     #[instrument(skip(app))]
     pub fn run(&self, app: &App) {
-        for workload_name in self.names.iter() {
-            let span = trace_span!("AppWorkload::run", ?workload_name);
-            let _span = span.enter();
-            app.world.run_workload(&workload_name).unwrap();
+        // the fixed steps are taken once per run, even if a cycle has several fixed stages
+        let mut fixed_steps = None;
+        let mut index = 0;
+        while index < self.names.len() {
+            let fixed_stage_len = self.names[index..]
+                .iter()
+                .take_while(|workload_name| self.fixed_names.contains(*workload_name))
+                .count();
+            if fixed_stage_len == 0 {
+                self.run_workload_name(app, &self.names[index]);
+                index += 1;
+                continue;
+            }
+
+            let fixed_stage = &self.names[index..index + fixed_stage_len];
+            let steps = *fixed_steps.get_or_insert_with(|| expend_fixed_timestep(&app.world));
+            for step in 0..steps {
+                let span = trace_span!("fixed step", step);
+                let _span = span.enter();
+                for workload_name in fixed_stage {
+                    self.run_workload_name(app, workload_name);
+                }
+            }
+            index += fixed_stage_len;
         }
         let mut all_storages = app.world.borrow::<AllStoragesViewMut>().unwrap();
         all_storages.clear_all_removed_or_deleted();
     }
+
+    fn run_workload_name(&self, app: &App, workload_name: &Cow<'static, str>) {
+        let span = trace_span!("AppWorkload::run", ?workload_name);
+        let _span = span.enter();
+        let workload_name = match self.conditions.get(workload_name) {
+            Some(condition) if !condition.should_run(&app.world) => return,
+            Some(condition) => condition.workload_to_run(&app.world, workload_name),
+            None => workload_name.clone(),
+        };
+        app.world.run_workload(&workload_name).unwrap();
+        apply_commands(&app.world);
+    }
 }
 
 impl<'a> AppBuilder<'a> {
@@ -270,12 +445,23 @@ impl<'a> AppBuilder<'a> {
     ///  3. Pull any data you need out from the [World], and repeat.
     ///
     /// # Panics
-    /// May panic if there are unmet unique dependencies or if there is an error adding workloads to shipyard.
+    /// Panics if any [BuildError] occurred, such as unmet unique dependencies, duplicate plugins or an error adding workloads to shipyard.
+    /// See [AppBuilder::try_finish] to handle these errors instead.
     #[track_caller]
     pub fn finish(self) -> AppWorkload {
         self.finish_with_info().0
     }
 
+    /// Fallible version of [AppBuilder::finish] which reports every [BuildError] found.
+    #[track_caller]
+    pub fn try_finish(self) -> Result<AppWorkload, Vec<BuildError>> {
+        self.try_finish_with_info_named(
+            DEFAULT_WORKLOAD_NAME.into(),
+            std::any::TypeId::of::<DefaultWorkloadPlugin>(),
+        )
+        .map(|(workload, _)| workload)
+    }
+
     /// Finish [App] and report back each of the update stages with their [AppWorkloadInfo].
     #[track_caller]
     fn finish_with_info(self) -> (AppWorkload, AppWorkloadInfo) {
@@ -287,45 +473,246 @@ impl<'a> AppBuilder<'a> {
 
     /// Finish [App] and report back each of the update stages with their [AppWorkloadInfo].
     #[track_caller]
-    #[instrument(skip(self))]
     pub(crate) fn finish_with_info_named(
         self,
         update_stage: std::borrow::Cow<'static, str>,
         plugin_id: TypeId,
     ) -> (AppWorkload, AppWorkloadInfo) {
+        match self.try_finish_with_info_named(update_stage.clone(), plugin_id) {
+            Ok(finished) => finished,
+            Err(errors) => panic!(
+                "Workload ({}) failed to build:\n{}",
+                update_stage,
+                errors
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            ),
+        }
+    }
+
+    /// Finish [App] and report back each of the update stages with their [AppWorkloadInfo], or every [BuildError] found.
+    #[track_caller]
+    #[instrument(skip(self))]
+    pub(crate) fn try_finish_with_info_named(
+        mut self,
+        update_stage: std::borrow::Cow<'static, str>,
+        plugin_id: TypeId,
+    ) -> Result<(AppWorkload, AppWorkloadInfo), Vec<BuildError>> {
+        self.add_pending_default_plugins();
+
+        let unmet_plugin_dependencies = self.unmet_plugin_dependencies();
+        self.errors.extend(unmet_plugin_dependencies);
+
+        let unmet_unique_dependencies = self.unmet_unique_dependencies();
+        self.errors.extend(
+            unmet_unique_dependencies
+                .into_iter()
+                .map(BuildError::MissingUnique),
+        );
+
+        let plugin_graph = PluginGraph::new(&self.track_added_plugins, &self.signature);
+
         let AppBuilder {
             app,
             resets,
             systems,
+            stages,
+            track_toggleable_plugins,
             track_added_plugins: _,
+            pending_default_plugins: _,
             track_current_plugin: _,
             track_type_names: _,
+            track_update_pack_resets: _,
+            unique_presence_checks: _,
+            mut errors,
             signature,
         } = self;
 
-        let mut update_workload = systems.into_iter().fold(
-            WorkloadBuilder::new(update_stage.clone()),
-            |acc: WorkloadBuilder, system: WorkloadSystem| acc.with_system(system),
-        );
+        let mut unordered_systems = stages
+            .iter()
+            .map(|stage| (*stage, Vec::new()))
+            .collect::<Vec<(&'static str, Vec<_>)>>();
+        for (system, provenance, ordering) in systems {
+            match unordered_systems
+                .iter_mut()
+                .find(|(stage, _)| *stage == provenance.stage)
+            {
+                Some((_, stage_systems)) => stage_systems.push((system, provenance, ordering)),
+                None => errors.push(BuildError::UnknownStage {
+                    stage: provenance.stage,
+                    plugin: provenance.plugin,
+                }),
+            }
+        }
+
+        let mut staged_systems = unordered_systems
+            .into_iter()
+            .filter_map(
+                |(stage, stage_systems)| match order_stage_systems(stage, stage_systems) {
+                    Ok(stage_systems) => Some((stage, stage_systems)),
+                    Err(order_errors) => {
+                        errors.extend(order_errors);
+                        None
+                    }
+                },
+            )
+            .collect::<Vec<_>>();
 
-        for reset_system in resets {
-            update_workload = update_workload.with_system(reset_system);
+        if !errors.is_empty() {
+            return Err(errors);
         }
 
-        let info = update_workload.add_to_world(&app.world).unwrap();
-        (
-            AppWorkload {
-                names: vec![update_stage],
-                // signature: Rc::new(signature),
-            },
+        // the update stage is always added so the workload name is always registered,
+        // other stages are only added if they have systems
+        staged_systems
+            .retain(|(stage, stage_groups)| *stage == stage::UPDATE || !stage_groups.is_empty());
+        if let Some((_, final_stage_groups)) = staged_systems.last_mut() {
+            if final_stage_groups.is_empty() {
+                final_stage_groups.push(Vec::new());
+            }
+            let final_group = final_stage_groups.last_mut().unwrap();
+            final_group.extend(resets);
+        }
+
+        if !track_toggleable_plugins.is_empty() {
+            register_toggleable_plugins(&app.world, &track_toggleable_plugins);
+        }
+
+        let mut names = Vec::with_capacity(staged_systems.len());
+        let mut conditions = HashMap::new();
+        let mut fixed_names = HashSet::new();
+        let mut stages_info = Vec::with_capacity(staged_systems.len());
+        let mut batch_info = Vec::new();
+        let mut systems_provenance = Vec::new();
+        for (stage, stage_groups) in staged_systems {
+            // systems of plugins which can be disabled only share a workload with systems toggled by the same
+            // plugins, so none of the storages of a toggleable workload are borrowed when it's skipped.
+            // a system ordered after a system of the current workload starts a new one, so it really runs after it.
+            // conditional systems stay in their workload, which is replaced by a variant without them when skipped
+            let mut segments: Vec<(Vec<(TypeId, &'static str)>, Vec<PendingSystem>)> = Vec::new();
+            for (index, (system, provenance)) in stage_groups
+                .into_iter()
+                .flat_map(|group| group.into_iter().enumerate())
+            {
+                systems_provenance.push(provenance);
+                match segments.last_mut() {
+                    Some((segment_toggled_by, segment_systems))
+                        if index > 0 && *segment_toggled_by == system.toggled_by =>
+                    {
+                        segment_systems.push(system)
+                    }
+                    _ => segments.push((system.toggled_by.clone(), vec![system])),
+                }
+            }
+            if segments.is_empty() {
+                segments.push((Vec::new(), Vec::new()));
+            }
+
+            let stage_name = stage_workload_name(&update_stage, stage);
+            let mut stage_info = StageInfo {
+                stage,
+                name: stage_name.clone(),
+                workloads: Vec::with_capacity(segments.len()),
+                batch_info: Vec::new(),
+            };
+            for (index, (toggled_by, segment_systems)) in segments.into_iter().enumerate() {
+                let segment_name: Cow<'static, str> = if index == 0 {
+                    stage_name.clone()
+                } else {
+                    format!("{}#{}", stage_name, index).into()
+                };
+                let mut segment_workload = WorkloadBuilder::new(segment_name.clone());
+                let mut gated_systems = Vec::with_capacity(segment_systems.len());
+                for PendingSystem {
+                    system,
+                    rebuild,
+                    condition,
+                    toggled_by: _,
+                } in segment_systems
+                {
+                    segment_workload = segment_workload.with_system(system);
+                    gated_systems.push((rebuild, condition));
+                }
+                let condition = WorkloadCondition {
+                    toggled_by,
+                    conditional: if gated_systems
+                        .iter()
+                        .any(|(_, condition)| condition.is_some())
+                    {
+                        Some(Arc::new(ConditionalSystems::new(gated_systems)))
+                    } else {
+                        None
+                    },
+                };
+
+                let info = segment_workload.add_to_world(&app.world).map_err(|error| {
+                    vec![BuildError::WorkloadRegistration {
+                        workload: segment_name.clone(),
+                        error,
+                    }]
+                })?;
+                stage_info.batch_info.extend(info.batch_info);
+                if !condition.is_empty() {
+                    conditions.insert(segment_name.clone(), condition);
+                }
+                if stage == stage::FIXED_UPDATE {
+                    fixed_names.insert(segment_name.clone());
+                }
+                stage_info.workloads.push(segment_name.clone());
+                names.push(segment_name);
+            }
+
+            batch_info.extend(stage_info.batch_info.iter().cloned());
+            stages_info.push(stage_info);
+        }
+
+        let workload = AppWorkload {
+            names,
+            conditions,
+            fixed_names,
+        };
+        app.add_command_queue();
+        app.set_default_workload_if_unset(&workload);
+
+        Ok((
+            workload,
             AppWorkloadInfo {
-                batch_info: info.batch_info,
+                batch_info,
+                stages: stages_info,
+                systems: systems_provenance,
                 type_names: Blind(app.type_names.clone()),
                 plugin_id,
-                name: info.name,
+                plugin_graph,
+                name: update_stage,
                 signature: Arc::new(signature),
             },
-        )
+        ))
+    }
+
+    /// Cross-check uniques declared with [AppBuilder::depends_on_unique] against the uniques provided
+    /// by plugins in this workload and the uniques already present in the [World].
+    fn unmet_unique_dependencies(&self) -> Vec<UnmetUniqueDependency> {
+        let provided = &self.signature.track_uniques_provided.type_plugins_lookup;
+        let tracked_provided = &self
+            .signature
+            .track_tracked_uniques_provided
+            .type_plugins_lookup;
+        self.signature
+            .track_unique_dependencies
+            .entries()
+            .into_iter()
+            .filter(|((unique_type_id, _), _)| {
+                !provided.contains_key(unique_type_id)
+                    && !tracked_provided.contains_key(unique_type_id)
+                    && !self
+                        .unique_presence_checks
+                        .get(unique_type_id)
+                        .map_or(false, |is_present| is_present(&self.app.world))
+            })
+            .map(|((_, unique), dependents)| UnmetUniqueDependency { unique, dependents })
+            .collect()
     }
 
     /// Lookup the type id while simultaneously storing the type name to be referenced later
@@ -333,11 +720,77 @@ impl<'a> AppBuilder<'a> {
         self.track_type_names.tracked_type_id_of::<T>()
     }
 
+    /// Add the [Events] unique for `E`, readable with [EventReader](crate::EventReader) and writable with
+    /// [EventWriter](crate::EventWriter), and swap its buffers among the last systems.
+    ///
+    /// Events are readable during the update after they were sent. Only the first workload adding the event swaps
+    /// its buffers: if [Events] of `E` are already in the [World], this workload depends on them instead, as if it
+    /// declared [AppBuilder::depends_on_unique], and relies on the other workload to swap them (see [App::add_cycle]).
+    #[track_caller]
+    pub fn add_event<E: Send + Sync + 'static>(&mut self) -> &mut Self {
+        let events_type_id = self.tracked_type_id_of::<Events<E>>();
+        let added_by_this_workload = self
+            .signature
+            .track_events
+            .type_plugins_lookup
+            .contains_key(&events_type_id);
+        if !added_by_this_workload && world_has_unique::<Events<E>>(&self.app.world) {
+            trace!(
+                plugin = ?self.track_current_plugin,
+                event = ?type_name::<E>(),
+                "events added by another workload"
+            );
+            return self
+                .depends_on_unique::<Events<E>>("add_event of events added by another workload");
+        }
+
+        if self
+            .signature
+            .track_events
+            .associate_plugin::<Events<E>>(&self.track_current_plugin, "add_event")
+            .is_first()
+        {
+            self.add_unique(Events::<E>::default());
+            if let Some(reset) =
+                self.workload_system(swap_events::<E>, stage::LAST, SystemKind::Reset)
+            {
+                self.resets.push(reset);
+            }
+        }
+
+        self
+    }
+
     /// Update component `T`'s storage to be update_pack, and add [shipyard::sparse_set::SparseSet::clear_all_inserted_and_modified] as the last system.
+    ///
+    /// The reset system is only added once per workload, no matter how many plugins require the update pack.
     #[track_caller]
     pub fn update_pack<T: Component<Tracking = track::All> + Send + Sync>(
         &mut self,
         reason: &'static str,
+    ) -> &mut Self {
+        self.update_pack_without_reset::<T>(reason);
+
+        let storage_type_id = self.tracked_type_id_of::<T>();
+        if self.track_update_pack_resets.insert(storage_type_id) {
+            if let Some(reset) =
+                self.workload_system(reset_update_pack::<T>, stage::LAST, SystemKind::Reset)
+            {
+                self.resets.push(reset);
+            }
+        }
+
+        self
+    }
+
+    /// Update component `T`'s storage to be update_pack, without adding a reset system.
+    ///
+    /// Use this when a plugin's own systems clear the tracking of `T` (for example, the tree indexing).
+    /// If another plugin in the workload uses [AppBuilder::update_pack] for `T`, the reset system is still added.
+    #[track_caller]
+    pub fn update_pack_without_reset<T: Component<Tracking = track::All> + Send + Sync>(
+        &mut self,
+        reason: &'static str,
     ) -> &mut Self {
         if self
             .signature
@@ -363,16 +816,23 @@ impl<'a> AppBuilder<'a> {
             .associate_plugin::<T>(&self.track_current_plugin, reason)
             .is_first()
         {
-            self.resets.push(
-                reset_tracked_unique::<T>
-                    .into_workload_system()
-                    .expect("system to be valid"),
-            );
+            if let Some(reset) =
+                self.workload_system(reset_tracked_unique::<T>, stage::LAST, SystemKind::Reset)
+            {
+                self.resets.push(reset);
+            }
         }
 
         self
     }
 
+    /// Set how [App::run_loop] drives the app, replacing any previously set [AppRunner]
+    pub fn set_runner(&mut self, runner: AppRunner) -> &mut Self {
+        trace!(plugin = ?self.track_current_plugin, ?runner, "set_runner");
+        self.app.set_runner(runner);
+        self
+    }
+
     /// Add a unique component
     #[track_caller]
     pub fn add_unique<T: Component>(&mut self, component: T) -> &mut Self
@@ -426,12 +886,18 @@ impl<'a> AppBuilder<'a> {
 
     /// Declare that this builder has a dependency on the following unique.
     ///
+    /// The dependency is satisfied if a plugin in this workload provides the unique (in any order) or if the unique
+    /// is already in the [World] (e.g. provided by a previously added workload).
+    ///
     /// If the unique dependency is not satisfied by the time [AppBuilder::finish] is called, then the finish call will panic.
     #[track_caller]
     pub fn depends_on_unique<T>(&mut self, dependency_reason: &'static str) -> &mut Self
     where
         T: Send + Sync + Component,
     {
+        let unique_type_id = self.tracked_type_id_of::<T>();
+        self.unique_presence_checks
+            .insert(unique_type_id, world_has_unique::<T>);
         self.signature
             .track_unique_dependencies
             .associate_plugin::<T>(&self.track_current_plugin, dependency_reason);
@@ -440,57 +906,301 @@ impl<'a> AppBuilder<'a> {
     }
 
     /// Declare that this builder has a dependency on the following plugin.
+    ///
+    /// The plugin may be added before or after this declaration. If the plugin has not been added by the time
+    /// [AppBuilder::finish] is called, then the finish call will panic (or [AppBuilder::try_finish] will report a [BuildError]).
     #[track_caller]
     pub fn depends_on_plugin<T>(&mut self, dependency_reason: &'static str) -> &mut Self
     where
         T: Plugin,
+    {
+        self.tracked_type_id_of::<T>();
+        self.signature
+            .track_plugin_dependencies
+            .associate_plugin::<T>(&self.track_current_plugin, dependency_reason);
+        self
+    }
+
+    /// Declare that this builder has a dependency on the following plugin, and add its [Default] if no other plugin adds it.
+    ///
+    /// The default is only added when finishing, so an explicitly configured `T` may still be added in any order.
+    #[track_caller]
+    pub fn depends_on_default_plugin<T>(&mut self, dependency_reason: &'static str) -> &mut Self
+    where
+        T: Plugin + Default,
     {
         let plugin_type_id = self.tracked_type_id_of::<T>();
-        if !self.track_added_plugins.contains_key(&plugin_type_id) {
-            panic!(
-                "\"{}\" depends on \"{}\": {}",
-                self.track_current_plugin,
-                type_name::<T>(),
-                dependency_reason
-            );
-        }
+        self.signature
+            .track_plugin_default_dependencies
+            .associate_plugin::<T>(&self.track_current_plugin, dependency_reason);
+        self.pending_default_plugins.push((
+            plugin_type_id,
+            add_default_plugin::<T> as fn(&mut AppBuilder),
+        ));
         self
     }
 
+    /// Add the defaults of plugins depended on with [AppBuilder::depends_on_default_plugin] which were never added.
+    fn add_pending_default_plugins(&mut self) {
+        // defaults may depend on further defaults, which are pushed while adding them
+        while !self.pending_default_plugins.is_empty() {
+            for (plugin_type_id, add_default) in std::mem::take(&mut self.pending_default_plugins) {
+                if self.track_added_plugins.contains_key(&plugin_type_id) {
+                    continue;
+                }
+
+                // added at the top level, the graph links it through the default dependency instead
+                let current_plugin = std::mem::take(&mut self.track_current_plugin);
+                add_default(self);
+                self.track_current_plugin = current_plugin;
+            }
+        }
+    }
+
+    /// Plugins declared with [AppBuilder::depends_on_plugin] which were never added.
+    fn unmet_plugin_dependencies(&self) -> Vec<BuildError> {
+        self.signature
+            .track_plugin_dependencies
+            .entries()
+            .into_iter()
+            .filter(|((plugin_type_id, _), _)| {
+                !self.track_added_plugins.contains_key(plugin_type_id)
+            })
+            .flat_map(|((_, dependency), dependents)| {
+                dependents
+                    .into_iter()
+                    .map(move |dependent| BuildError::MissingPluginDependency {
+                        dependency,
+                        dependent,
+                    })
+            })
+            .collect()
+    }
+
     fn empty(app: &App) -> AppBuilder<'_> {
         AppBuilder {
             app,
             resets: Vec::new(),
             systems: Vec::new(),
+            stages: stage::DEFAULT_STAGES.to_vec(),
+            track_toggleable_plugins: HashMap::new(),
             track_added_plugins: Default::default(),
+            pending_default_plugins: Vec::new(),
             track_current_plugin: Default::default(),
             track_type_names: Default::default(),
+            track_update_pack_resets: Default::default(),
+            unique_presence_checks: Default::default(),
+            errors: Vec::new(),
             signature: WorkloadSignature::new(&app.type_names),
         }
     }
 
+    /// Add a system to the [stage::UPDATE] stage
+    #[track_caller]
+    pub fn add_system<B, R, S: AppSystem<B, R>>(&mut self, system: S) -> &mut Self {
+        self.add_system_to_stage(stage::UPDATE, system)
+    }
+
+    /// Add a system to a built-in [stage] or a stage added by a plugin.
+    ///
+    /// The stage may be added by another plugin after this call, but it must exist by the time the builder finishes.
+    #[track_caller]
+    pub fn add_system_to_stage<B, R, S: AppSystem<B, R>>(
+        &mut self,
+        stage: &'static str,
+        system: S,
+    ) -> &mut Self {
+        self.add_system_to_stage_labeled(stage, &[], system);
+        self
+    }
+
+    /// Add a system to the [stage::UPDATE] stage which is skipped whenever `condition` returns `false`.
+    ///
+    /// Conditions are systems returning `bool`, such as [tracked_unique_modified](crate::tracked_unique_modified),
+    /// [storage_inserted_or_modified](crate::storage_inserted_or_modified) or [unique_flag_set](crate::unique_flag_set).
+    /// Each condition is checked when the workload of its system is about to run, so a condition reading what a
+    /// system of the same workload writes sees it as it was before that workload started. A condition which can't
+    /// borrow what it needs (such as a missing unique) skips its system with a warning.
+    /// The system stays batched with the rest of its stage: when some conditions are not met, a variant of the
+    /// workload without the skipped systems runs instead, so they borrow nothing. Each variant is added to the
+    /// [World] the first time it's needed.
+    #[track_caller]
+    pub fn add_system_if<B, R, S, CB: 'static, C>(&mut self, system: S, condition: C) -> &mut Self
+    where
+        S: AppSystem<B, R>,
+        C: for<'s> System<'s, (), CB, bool> + Clone + Send + Sync + 'static,
+    {
+        self.add_system_to_stage_labeled(stage::UPDATE, &[], system)
+            .run_if(condition);
+        self
+    }
+
+    /// Add a system to the [stage::FIXED_UPDATE] stage, which runs once per [FixedTimestep] step.
+    ///
+    /// Adds the default [FixedTimestep] and [Time] uniques if they are missing. [Time] must be advanced every update
+    /// for fixed steps to accumulate, by a [TimePlugin] or by hand with [Time::advance].
+    #[track_caller]
+    pub fn add_fixed_system<B, R, S: AppSystem<B, R>>(&mut self, system: S) -> &mut Self {
+        if !world_has_unique::<FixedTimestep>(&self.app.world) {
+            self.add_unique(FixedTimestep::default());
+        }
+        if !world_has_unique::<Time>(&self.app.world) {
+            self.add_unique(Time::default());
+        }
+
+        self.add_system_to_stage(stage::FIXED_UPDATE, system)
+    }
+
+    /// Configure the step of [AppBuilder::add_fixed_system] systems, replacing the current [FixedTimestep]
+    pub fn set_fixed_timestep(&mut self, fixed_timestep: FixedTimestep) -> &mut Self {
+        let app = self.app;
+        match app.world.borrow::<UniqueViewMut<FixedTimestep>>() {
+            Ok(mut existing) => *existing = fixed_timestep,
+            Err(_) => {
+                self.add_unique(fixed_timestep);
+            }
+        }
+
+        self
+    }
+
+    /// Add a labeled system to the [stage::UPDATE] stage, which other systems can be ordered against.
+    ///
+    /// Use the returned [SystemConfig] to order this system before or after other labels:
+    /// `app.add_system_labeled("index_tree", tree_indexing).after("reorder_tree");`
+    #[track_caller]
+    pub fn add_system_labeled<B, R, S: AppSystem<B, R>>(
+        &mut self,
+        label: &'static str,
+        system: S,
+    ) -> SystemConfig<'_, 'a> {
+        self.add_system_to_stage_labeled(stage::UPDATE, &[label], system)
+    }
+
+    /// Add a system with any number of labels to a stage, returning a [SystemConfig] to order it within that stage.
+    #[track_caller]
+    pub fn add_system_to_stage_labeled<B, R, S: AppSystem<B, R>>(
+        &mut self,
+        stage: &'static str,
+        labels: &[&'static str],
+        system: S,
+    ) -> SystemConfig<'_, 'a> {
+        let index = match self.workload_system(system, stage, SystemKind::Update) {
+            Some((mut system, mut provenance)) => {
+                provenance.labels.extend_from_slice(labels);
+                system.toggled_by = provenance
+                    .plugin
+                    .iter()
+                    .filter(|(type_id, _)| self.track_toggleable_plugins.contains_key(type_id))
+                    .collect();
+                self.systems
+                    .push((system, provenance, SystemOrdering::default()));
+                Some(self.systems.len() - 1)
+            }
+            None => None,
+        };
+
+        SystemConfig {
+            builder: self,
+            index,
+        }
+    }
+
+    /// Add a new stage which runs before the `target` stage
+    #[track_caller]
+    pub fn add_stage_before(&mut self, target: &'static str, stage: &'static str) -> &mut Self {
+        self.add_stage_relative(target, stage, 0)
+    }
+
+    /// Add a new stage which runs after the `target` stage
     #[track_caller]
-    pub fn add_system<B, R, S: IntoWorkloadSystem<B, R>>(&mut self, system: S) -> &mut Self {
-        self.systems
-            .push(system.into_workload_system().expect("system to be valid"));
+    pub fn add_stage_after(&mut self, target: &'static str, stage: &'static str) -> &mut Self {
+        self.add_stage_relative(target, stage, 1)
+    }
+
+    fn add_stage_relative(
+        &mut self,
+        target: &'static str,
+        stage: &'static str,
+        offset: usize,
+    ) -> &mut Self {
+        if self.stages.contains(&stage) {
+            trace!(plugin = ?self.track_current_plugin, ?stage, "stage already added");
+            return self;
+        }
+
+        match self.stages.iter().position(|existing| *existing == target) {
+            Some(target_index) => self.stages.insert(target_index + offset, stage),
+            None => self.errors.push(BuildError::UnknownStage {
+                stage: target,
+                plugin: self.track_current_plugin.clone(),
+            }),
+        }
 
         self
     }
 
     /// Ensure that this system is among the absolute last systems
     #[track_caller]
-    pub fn add_reset_system<B, R, S: IntoWorkloadSystem<B, R>>(
+    pub fn add_reset_system<B, R, S: AppSystem<B, R>>(
         &mut self,
         system: S,
         reason: &str,
     ) -> &mut Self {
         trace!(plugin = ?self.track_current_plugin, ?reason, "add_reset_system");
-        self.resets
-            .push(system.into_workload_system().expect("system to be valid"));
+        if let Some(system) = self.workload_system(system, stage::LAST, SystemKind::Reset) {
+            self.resets.push(system);
+        }
 
         self
     }
 
+    /// Convert into a [WorkloadSystem] tagged with the current plugin, recording a [BuildError::InvalidSystem] on failure
+    #[track_caller]
+    fn workload_system<B, R, S>(
+        &mut self,
+        system: S,
+        stage: &'static str,
+        kind: SystemKind,
+    ) -> Option<(PendingSystem, SystemProvenance)>
+    where
+        S: AppSystem<B, R>,
+    {
+        let provenance = SystemProvenance {
+            system: type_name::<S>(),
+            plugin: self.track_current_plugin.clone(),
+            location: Location::caller(),
+            stage,
+            labels: Vec::new(),
+            run_condition: None,
+            kind,
+        };
+        match system.clone().into_workload_system() {
+            Ok(workload_system) => Some((
+                PendingSystem {
+                    system: workload_system,
+                    rebuild: Arc::new(move || {
+                        system
+                            .clone()
+                            .into_workload_system()
+                            .expect("system was valid when added")
+                    }),
+                    condition: None,
+                    toggled_by: Vec::new(),
+                },
+                provenance,
+            )),
+            Err(error) => {
+                self.errors.push(BuildError::InvalidSystem {
+                    system: type_name::<S>(),
+                    plugin: self.track_current_plugin.clone(),
+                    error,
+                });
+                None
+            }
+        }
+    }
+
     #[track_caller]
     pub fn add_plugin<T>(&mut self, plugin: T) -> &mut Self
     where
@@ -501,21 +1211,32 @@ impl<'a> AppBuilder<'a> {
         let _span = span.enter();
         if let Some(plugin_id) = self.track_added_plugins.get(&plugin_type_id) {
             if !plugin.can_add_multiple_times() {
-                panic!(
-                    "Plugin ({}) cannot add plugin as it's already added as \"{}\". (Implement `Plugin::can_add_multiple_times` to override)",
-                    self.track_current_plugin, plugin_id
-                );
+                self.errors.push(BuildError::DuplicatePlugin {
+                    plugin: type_name::<T>(),
+                    existing: plugin_id.clone(),
+                    added_by: self.track_current_plugin.clone(),
+                });
+                return self;
             }
         }
 
         if self.track_current_plugin.contains(plugin_type_id) {
-            panic!(
-                "Plugin ({}) cannot add plugin ({}) as it would cause a cycle",
-                self.track_current_plugin,
-                self.track_type_names
-                    .lookup_name(&plugin_type_id)
-                    .unwrap_or(""),
-            );
+            self.errors.push(BuildError::PluginCycle {
+                plugin: type_name::<T>(),
+                added_by: self.track_current_plugin.clone(),
+            });
+            return self;
+        }
+
+        if !self.track_current_plugin.is_empty() {
+            self.signature
+                .track_plugins_added
+                .associate_plugin::<T>(&self.track_current_plugin, "adds");
+        }
+
+        if plugin.can_be_disabled() {
+            self.track_toggleable_plugins
+                .insert(TypeId::of::<T>(), type_name::<T>());
         }
 
         self.track_current_plugin.push::<T>();
@@ -528,3 +1249,958 @@ impl<'a> AppBuilder<'a> {
         self
     }
 }
+
+/// The update stage keeps the workload's name, other stages are suffixed with their stage name
+fn stage_workload_name(
+    workload_name: &Cow<'static, str>,
+    stage: &'static str,
+) -> Cow<'static, str> {
+    if stage == stage::UPDATE {
+        workload_name.clone()
+    } else {
+        format!("{}::{}", workload_name, stage).into()
+    }
+}
+
+/// Make the plugins of this workload toggleable through the [PluginToggles] unique
+fn register_toggleable_plugins(world: &World, plugins: &HashMap<TypeId, &'static str>) {
+    if let Ok(mut toggles) = world.borrow::<UniqueViewMut<PluginToggles>>() {
+        for (type_id, name) in plugins {
+            toggles.register(*type_id, name);
+        }
+        return;
+    }
+
+    let mut toggles = PluginToggles::default();
+    for (type_id, name) in plugins {
+        toggles.register(*type_id, name);
+    }
+    world.add_unique(toggles).unwrap();
+}
+
+fn world_has_unique<T: Send + Sync + Component>(world: &World) -> bool {
+    world.borrow::<UniqueView<T>>().is_ok()
+}
+
+fn add_default_plugin<T: Plugin + Default>(builder: &mut AppBuilder) {
+    builder.add_plugin(T::default());
+}
+
+#[cfg(test)]
+mod unique_dependency_tests {
+    use super::*;
+
+    #[derive(Component)]
+    struct U;
+    struct DependsOnU;
+    struct ProvidesU;
+    struct DependsOnUThenProvidesU;
+
+    impl Plugin for DependsOnU {
+        fn build(&self, app: &mut AppBuilder) {
+            app.depends_on_unique::<U>("DependsOnU reads U");
+        }
+    }
+
+    impl Plugin for ProvidesU {
+        fn build(&self, app: &mut AppBuilder) {
+            app.add_unique(U);
+        }
+    }
+
+    impl Plugin for DependsOnUThenProvidesU {
+        fn build(&self, app: &mut AppBuilder) {
+            app.add_plugin(DependsOnU).add_plugin(ProvidesU);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "DependsOnU reads U")]
+    fn test_unmet_unique_dependency_panics() {
+        let mut app = App::new();
+
+        app.add_plugin_workload(DependsOnU);
+    }
+
+    #[test]
+    fn test_unique_provided_later_in_workload() {
+        let mut app = App::new();
+
+        app.add_plugin_workload(DependsOnUThenProvidesU);
+    }
+
+    #[test]
+    fn test_unique_already_in_world() {
+        let mut app = App::new();
+        app.world.add_unique(U).unwrap();
+
+        app.add_plugin_workload(DependsOnU);
+    }
+}
+
+#[cfg(test)]
+mod build_error_tests {
+    use super::*;
+
+    #[derive(Component)]
+    struct U;
+    struct Leaf;
+    struct AddsLeafTwice;
+    struct CycleA;
+    struct CycleB;
+    struct DependsOnLeaf;
+    struct DependsOnU;
+
+    impl Plugin for Leaf {
+        fn build(&self, _: &mut AppBuilder) {}
+    }
+
+    impl Plugin for AddsLeafTwice {
+        fn build(&self, app: &mut AppBuilder) {
+            app.add_plugin(Leaf).add_plugin(Leaf);
+        }
+    }
+
+    impl Plugin for CycleA {
+        fn build(&self, app: &mut AppBuilder) {
+            app.add_plugin(CycleB);
+        }
+    }
+
+    impl Plugin for CycleB {
+        fn build(&self, app: &mut AppBuilder) {
+            app.add_plugin(CycleA);
+        }
+    }
+
+    impl Plugin for DependsOnLeaf {
+        fn build(&self, app: &mut AppBuilder) {
+            app.depends_on_plugin::<Leaf>("needs Leaf");
+        }
+    }
+
+    impl Plugin for DependsOnU {
+        fn build(&self, app: &mut AppBuilder) {
+            app.depends_on_unique::<U>("needs U");
+        }
+    }
+
+    #[test]
+    fn test_duplicate_plugin() {
+        let mut app = App::new();
+
+        let errors = app
+            .try_add_plugin_workload(AddsLeafTwice)
+            .expect_err("expected duplicate plugin");
+
+        assert_eq!(errors.len(), 1, "{:#?}", errors);
+        assert!(
+            matches!(errors[0], BuildError::DuplicatePlugin { plugin, .. } if plugin == type_name::<Leaf>()),
+            "{:#?}",
+            errors
+        );
+    }
+
+    #[test]
+    fn test_plugin_cycle() {
+        let mut app = App::new();
+
+        let errors = app
+            .try_add_plugin_workload(CycleA)
+            .expect_err("expected plugin cycle");
+
+        assert_eq!(errors.len(), 1, "{:#?}", errors);
+        assert!(
+            matches!(errors[0], BuildError::PluginCycle { plugin, .. } if plugin == type_name::<CycleA>()),
+            "{:#?}",
+            errors
+        );
+    }
+
+    #[test]
+    fn test_reports_every_error() {
+        let app = App::new();
+        let mut builder = AppBuilder::new(&app);
+        builder.add_plugin(DependsOnLeaf).add_plugin(DependsOnU);
+
+        let errors = builder.try_finish().expect_err("expected errors");
+
+        assert_eq!(errors.len(), 2, "{:#?}", errors);
+        assert!(
+            matches!(&errors[0], BuildError::MissingPluginDependency { dependency, dependent } if *dependency == type_name::<Leaf>() && dependent.reason == "needs Leaf"),
+            "{:#?}",
+            errors
+        );
+        assert!(
+            matches!(&errors[1], BuildError::MissingUnique(unmet) if unmet.unique == type_name::<U>()),
+            "{:#?}",
+            errors
+        );
+    }
+
+    #[test]
+    fn test_failed_workload_releases_its_name() {
+        let mut app = App::new();
+        app.try_add_plugin_workload(DependsOnU)
+            .expect_err("expected missing unique");
+
+        app.world.add_unique(U).unwrap();
+        let (_, info) = app
+            .try_add_plugin_workload_with_info(DependsOnU)
+            .expect("unique added");
+        assert_eq!(info.name, type_name::<DependsOnU>());
+    }
+}
+
+#[cfg(test)]
+mod update_pack_tests {
+    use super::*;
+
+    #[derive(Component)]
+    #[track(All)]
+    struct A;
+    struct UpdatePacksA;
+
+    impl Plugin for UpdatePacksA {
+        fn build(&self, app: &mut AppBuilder) {
+            app.update_pack::<A>("UpdatePacksA");
+        }
+    }
+
+    #[test]
+    fn test_update_pack_resets_tracking() {
+        let mut app = App::new();
+        app.add_plugin_workload(UpdatePacksA);
+
+        app.run(|mut entities: EntitiesViewMut, mut vm_a: ViewMut<A>| {
+            entities.add_entity(&mut vm_a, A);
+        });
+
+        app.update();
+
+        app.run(|v_a: View<A>| {
+            assert_eq!(v_a.len(), 1);
+            assert_eq!(
+                v_a.inserted_or_modified().iter().count(),
+                0,
+                "expected tracking to be reset at the end of the update"
+            );
+        });
+    }
+}
+
+#[cfg(test)]
+mod plugin_graph_tests {
+    use super::*;
+
+    struct Root;
+    struct Leaf;
+    struct DependsOnLeaf;
+
+    impl Plugin for Root {
+        fn build(&self, app: &mut AppBuilder) {
+            app.add_plugin(Leaf).add_plugin(DependsOnLeaf);
+        }
+    }
+
+    impl Plugin for Leaf {
+        fn build(&self, _: &mut AppBuilder) {}
+    }
+
+    impl Plugin for DependsOnLeaf {
+        fn build(&self, app: &mut AppBuilder) {
+            app.depends_on_plugin::<Leaf>("needs Leaf");
+        }
+    }
+
+    #[test]
+    fn test_plugin_graph() {
+        let mut app = App::new();
+
+        let (_, info) = app.add_plugin_workload_with_info(Root);
+        let graph = info.plugin_graph();
+
+        assert_eq!(graph.nodes().len(), 3, "{:#?}", graph);
+        assert!(graph.contains::<Root>());
+        assert_eq!(
+            graph.added_by::<Root>(),
+            vec![type_name::<DependsOnLeaf>(), type_name::<Leaf>()]
+        );
+        assert_eq!(
+            graph.dependencies_of::<DependsOnLeaf>(),
+            vec![type_name::<Leaf>()]
+        );
+        assert_eq!(
+            graph.dependents_of::<Leaf>(),
+            vec![type_name::<DependsOnLeaf>()]
+        );
+        let dependency = graph
+            .edges_to::<Leaf>()
+            .find(|edge| edge.kind == PluginEdgeKind::DependsOn)
+            .expect("dependency edge");
+        assert_eq!(dependency.reason, "needs Leaf");
+    }
+}
+
+#[cfg(test)]
+mod plugin_dependency_tests {
+    use super::*;
+
+    #[derive(Component)]
+    struct Configured(u32);
+    struct DependsOnLeaf;
+    struct DependsOnDefaultLeaf;
+    struct Leaf(u32);
+
+    impl Default for Leaf {
+        fn default() -> Self {
+            Leaf(1)
+        }
+    }
+
+    impl Plugin for Leaf {
+        fn build(&self, app: &mut AppBuilder) {
+            app.add_unique(Configured(self.0));
+        }
+    }
+
+    impl Plugin for DependsOnLeaf {
+        fn build(&self, app: &mut AppBuilder) {
+            app.depends_on_plugin::<Leaf>("needs Leaf");
+        }
+    }
+
+    impl Plugin for DependsOnDefaultLeaf {
+        fn build(&self, app: &mut AppBuilder) {
+            app.depends_on_default_plugin::<Leaf>("needs Leaf");
+        }
+    }
+
+    #[test]
+    fn test_dependency_added_after_dependent() {
+        let app = App::new();
+        let mut builder = AppBuilder::new(&app);
+        builder.add_plugin(DependsOnLeaf).add_plugin(Leaf(2));
+
+        builder.try_finish().expect("dependency satisfied");
+    }
+
+    #[test]
+    fn test_default_dependency_added_when_absent() {
+        let app = App::new();
+        let mut builder = AppBuilder::new(&app);
+        builder.add_plugin(DependsOnDefaultLeaf);
+
+        builder.try_finish().expect("default dependency added");
+        assert_eq!(app.world.borrow::<UniqueView<Configured>>().unwrap().0, 1);
+    }
+
+    #[test]
+    fn test_default_dependency_recorded_in_graph() {
+        let mut app = App::new();
+
+        let (_, info) = app.add_plugin_workload_with_info(DependsOnDefaultLeaf);
+        let graph = info.plugin_graph();
+
+        assert!(graph.added_by::<DependsOnDefaultLeaf>().is_empty());
+        assert_eq!(
+            graph.dependencies_of::<DependsOnDefaultLeaf>(),
+            vec![type_name::<Leaf>()]
+        );
+        let dependency = graph
+            .edges_to::<Leaf>()
+            .find(|edge| edge.kind == PluginEdgeKind::DependsOnDefault)
+            .expect("default dependency edge");
+        assert_eq!(dependency.reason, "needs Leaf");
+    }
+
+    #[test]
+    fn test_default_dependency_prefers_explicit_plugin() {
+        let app = App::new();
+        let mut builder = AppBuilder::new(&app);
+        builder.add_plugin(DependsOnDefaultLeaf).add_plugin(Leaf(2));
+
+        builder.try_finish().expect("explicit dependency used");
+        assert_eq!(app.world.borrow::<UniqueView<Configured>>().unwrap().0, 2);
+    }
+}
+
+#[cfg(test)]
+mod system_provenance_tests {
+    use super::*;
+
+    #[derive(Component)]
+    #[track(All)]
+    struct A;
+    struct Root;
+    struct Leaf;
+
+    fn leaf_system(_: View<A>) {}
+
+    impl Plugin for Root {
+        fn build(&self, app: &mut AppBuilder) {
+            app.add_plugin(Leaf).tracks::<A>("Root tracks A");
+        }
+    }
+
+    impl Plugin for Leaf {
+        fn build(&self, app: &mut AppBuilder) {
+            app.add_system(leaf_system);
+        }
+    }
+
+    #[test]
+    fn test_system_provenance() {
+        let mut app = App::new();
+        app.world.add_unique(A).unwrap();
+
+        let (_, info) = app.add_plugin_workload_with_info(Root);
+
+        let leaf = info
+            .systems()
+            .iter()
+            .find(|provenance| provenance.system.ends_with("leaf_system"))
+            .expect("leaf_system provenance");
+        assert_eq!(leaf.kind, SystemKind::Update);
+        assert_eq!(
+            leaf.plugin.to_string(),
+            format!("{} → {}", type_name::<Root>(), type_name::<Leaf>())
+        );
+        assert_eq!(leaf.location.file(), file!());
+
+        let reset = info
+            .systems()
+            .iter()
+            .find(|provenance| provenance.kind == SystemKind::Reset)
+            .expect("reset tracked unique provenance");
+        assert_eq!(reset.plugin.to_string(), type_name::<Root>());
+
+        for batch in info.batches_with_provenance() {
+            for (system_info, provenance) in batch {
+                assert!(
+                    provenance.is_some(),
+                    "expected {} to be attributed",
+                    system_info.name
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod stage_tests {
+    use super::*;
+
+    #[derive(Component, Default)]
+    struct Order(Vec<&'static str>);
+    struct StagesPlugin;
+    struct UnknownStagePlugin;
+
+    const AFTER_FIRST: &str = "after_first";
+
+    fn push_update(mut order: UniqueViewMut<Order>) {
+        order.0.push(stage::UPDATE);
+    }
+    fn push_first(mut order: UniqueViewMut<Order>) {
+        order.0.push(stage::FIRST);
+    }
+    fn push_after_first(mut order: UniqueViewMut<Order>) {
+        order.0.push(AFTER_FIRST);
+    }
+    fn push_last(mut order: UniqueViewMut<Order>) {
+        order.0.push(stage::LAST);
+    }
+
+    impl Plugin for StagesPlugin {
+        fn build(&self, app: &mut AppBuilder) {
+            app.add_system_to_stage(stage::LAST, push_last)
+                .add_system(push_update)
+                .add_system_to_stage(AFTER_FIRST, push_after_first)
+                .add_stage_after(stage::FIRST, AFTER_FIRST)
+                .add_system_to_stage(stage::FIRST, push_first);
+        }
+    }
+
+    impl Plugin for UnknownStagePlugin {
+        fn build(&self, app: &mut AppBuilder) {
+            app.add_system_to_stage("missing", push_update)
+                .add_stage_before("also_missing", AFTER_FIRST);
+        }
+    }
+
+    #[test]
+    fn test_stages_run_in_order() {
+        let mut app = App::new();
+        app.world.add_unique(Order::default()).unwrap();
+
+        let (workload, info) = app.add_plugin_workload_with_info(StagesPlugin);
+        assert_eq!(
+            info.stages()
+                .iter()
+                .map(|stage| stage.stage)
+                .collect::<Vec<_>>(),
+            vec![stage::FIRST, AFTER_FIRST, stage::UPDATE, stage::LAST]
+        );
+        assert_eq!(workload.names.len(), 4);
+
+        app.update();
+        assert_eq!(
+            app.world.borrow::<UniqueView<Order>>().unwrap().0,
+            vec![stage::FIRST, AFTER_FIRST, stage::UPDATE, stage::LAST]
+        );
+    }
+
+    #[test]
+    fn test_world_default_workload_can_be_used_again() {
+        let mut app = App::new();
+        app.world.add_unique(Order::default()).unwrap();
+        app.add_plugin_workload(StagesPlugin);
+        WorkloadBuilder::new("world_default")
+            .with_system(push_update)
+            .add_to_world(&app.world)
+            .unwrap();
+        app.world.set_default_workload("world_default").unwrap();
+
+        app.use_world_default_workload();
+        app.update();
+        assert_eq!(
+            app.world.borrow::<UniqueView<Order>>().unwrap().0,
+            vec![stage::UPDATE]
+        );
+    }
+
+    #[test]
+    fn test_unknown_stages_are_reported() {
+        let mut app = App::new();
+        app.world.add_unique(Order::default()).unwrap();
+
+        let errors = app
+            .try_add_plugin_workload(UnknownStagePlugin)
+            .expect_err("unknown stages");
+        let stages = errors
+            .iter()
+            .filter_map(|error| match error {
+                BuildError::UnknownStage { stage, .. } => Some(*stage),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(stages, vec!["also_missing", "missing"]);
+    }
+}
+
+#[cfg(test)]
+mod system_order_tests {
+    use super::*;
+
+    #[derive(Component, Default)]
+    struct Order(Vec<&'static str>);
+    /// Shared borrows only, so systems pushing to it don't conflict
+    #[derive(Component, Default)]
+    struct SharedOrder(std::sync::Mutex<Vec<&'static str>>);
+    struct OrderedPlugin;
+    struct NonConflictingPlugin;
+    struct CyclePlugin;
+    struct UnknownLabelPlugin;
+
+    fn push_a(mut order: UniqueViewMut<Order>) {
+        order.0.push("a");
+    }
+    fn push_b(mut order: UniqueViewMut<Order>) {
+        order.0.push("b");
+    }
+    fn push_c(mut order: UniqueViewMut<Order>) {
+        order.0.push("c");
+    }
+    fn push_d(mut order: UniqueViewMut<Order>) {
+        order.0.push("d");
+    }
+    fn push_early(order: UniqueView<SharedOrder>) {
+        order.0.lock().unwrap().push("early");
+    }
+    fn push_late(order: UniqueView<SharedOrder>) {
+        order.0.lock().unwrap().push("late");
+    }
+
+    impl Plugin for OrderedPlugin {
+        fn build(&self, app: &mut AppBuilder) {
+            app.add_system_labeled("c", push_c)
+                .after("b")
+                .add_system_labeled("a", push_a)
+                .add_system_labeled("b", push_b)
+                .after("a")
+                .add_system_labeled("d", push_d)
+                .before("a");
+        }
+    }
+
+    impl Plugin for NonConflictingPlugin {
+        fn build(&self, app: &mut AppBuilder) {
+            app.add_unique(SharedOrder::default())
+                .add_system_labeled("late", push_late)
+                .after("early")
+                .add_system_labeled("early", push_early);
+        }
+    }
+
+    impl Plugin for CyclePlugin {
+        fn build(&self, app: &mut AppBuilder) {
+            app.add_system_labeled("a", push_a)
+                .after("b")
+                .add_system_labeled("b", push_b)
+                .after("a")
+                .add_system(push_c);
+        }
+    }
+
+    impl Plugin for UnknownLabelPlugin {
+        fn build(&self, app: &mut AppBuilder) {
+            app.add_system_labeled("a", push_a).after("missing");
+        }
+    }
+
+    #[test]
+    fn test_systems_follow_constraints() {
+        let mut app = App::new();
+        app.world.add_unique(Order::default()).unwrap();
+
+        let (_, info) = app.add_plugin_workload_with_info(OrderedPlugin);
+        assert_eq!(
+            info.systems()
+                .iter()
+                .map(|provenance| provenance.labels.clone())
+                .collect::<Vec<_>>(),
+            vec![vec!["d"], vec!["a"], vec!["b"], vec!["c"]]
+        );
+
+        app.update();
+        assert_eq!(
+            app.world.borrow::<UniqueView<Order>>().unwrap().0,
+            vec!["d", "a", "b", "c"]
+        );
+    }
+
+    #[test]
+    fn test_constraints_hold_without_conflicting_borrows() {
+        let mut app = App::new();
+
+        let (_, info) = app.add_plugin_workload_with_info(NonConflictingPlugin);
+        // batched together they could run in any order, in consecutive workloads they can't
+        let stage = &info.stages()[0];
+        assert_eq!(stage.workloads.len(), 2);
+        let batches = &stage.batch_info;
+        assert_eq!(batches.len(), 2);
+        let names = batches
+            .iter()
+            .flat_map(|batch| batch.systems.iter().map(|system| system.name))
+            .collect::<Vec<_>>();
+        assert!(names[0].ends_with("push_early"), "{:?}", names);
+        assert!(names[1].ends_with("push_late"), "{:?}", names);
+    }
+
+    #[test]
+    fn test_cyclic_constraints_are_reported() {
+        let mut app = App::new();
+        app.world.add_unique(Order::default()).unwrap();
+
+        let errors = app
+            .try_add_plugin_workload(CyclePlugin)
+            .expect_err("cyclic constraints");
+        match &errors[..] {
+            [BuildError::SystemOrderCycle { stage, systems }] => {
+                assert_eq!(*stage, stage::UPDATE);
+                assert_eq!(
+                    systems
+                        .iter()
+                        .map(|provenance| provenance.labels.clone())
+                        .collect::<Vec<_>>(),
+                    vec![vec!["a"], vec!["b"]]
+                );
+            }
+            other => panic!("expected a single cycle error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_unknown_labels_are_reported() {
+        let mut app = App::new();
+        app.world.add_unique(Order::default()).unwrap();
+
+        let errors = app
+            .try_add_plugin_workload(UnknownLabelPlugin)
+            .expect_err("unknown label");
+        assert!(matches!(
+            &errors[..],
+            [BuildError::UnknownSystemLabel {
+                label: "missing",
+                ..
+            }]
+        ));
+    }
+}
+
+#[cfg(test)]
+mod run_condition_tests {
+    use super::*;
+    use crate::{storage_inserted_or_modified, unique_flag_set};
+
+    #[derive(Component, Default)]
+    struct Runs(u32);
+    #[derive(Clone, Component)]
+    struct Enabled(bool);
+    #[derive(Component)]
+    #[track(All)]
+    struct A;
+    struct FlagPlugin;
+    struct StoragePlugin;
+    struct SharedStagePlugin;
+    struct MissingFlagPlugin;
+    /// Never added to the world
+    #[derive(Clone, Component)]
+    struct Missing(bool);
+
+    impl From<Enabled> for bool {
+        fn from(enabled: Enabled) -> bool {
+            enabled.0
+        }
+    }
+
+    impl From<Missing> for bool {
+        fn from(missing: Missing) -> bool {
+            missing.0
+        }
+    }
+
+    fn count_runs(mut runs: UniqueViewMut<Runs>) {
+        runs.0 += 1;
+    }
+    fn read_a(_v_a: View<A>) {}
+
+    impl Plugin for FlagPlugin {
+        fn build(&self, app: &mut AppBuilder) {
+            app.add_unique(Runs::default())
+                .add_unique(Enabled(false))
+                .add_system_if(count_runs, unique_flag_set::<Enabled>);
+        }
+    }
+
+    impl Plugin for StoragePlugin {
+        fn build(&self, app: &mut AppBuilder) {
+            app.add_unique(Runs::default())
+                .update_pack::<A>("counts runs when A changes")
+                .add_system_if(count_runs, storage_inserted_or_modified::<A>);
+        }
+    }
+
+    impl Plugin for SharedStagePlugin {
+        fn build(&self, app: &mut AppBuilder) {
+            app.add_unique(Runs::default())
+                .add_unique(Enabled(true))
+                .add_system(read_a)
+                .add_system_if(count_runs, unique_flag_set::<Enabled>)
+                .add_system_if(read_a, unique_flag_set::<Enabled>)
+                .add_system(read_a);
+        }
+    }
+
+    impl Plugin for MissingFlagPlugin {
+        fn build(&self, app: &mut AppBuilder) {
+            app.add_unique(Runs::default())
+                .add_system_if(count_runs, unique_flag_set::<Missing>);
+        }
+    }
+
+    #[test]
+    fn test_conditional_systems_share_their_stage() {
+        let mut app = App::new();
+        let (_, info) = app.add_plugin_workload_with_info(SharedStagePlugin);
+
+        // none of the systems conflict, so conditional or not they run in a single batch
+        assert_eq!(info.stages()[0].workloads.len(), 1);
+        assert_eq!(info.batch_info().len(), 1);
+
+        app.update();
+        assert_eq!(app.world.borrow::<UniqueView<Runs>>().unwrap().0, 1);
+
+        app.world.borrow::<UniqueViewMut<Enabled>>().unwrap().0 = false;
+        app.update();
+        app.update();
+        assert_eq!(app.world.borrow::<UniqueView<Runs>>().unwrap().0, 1);
+
+        app.world.borrow::<UniqueViewMut<Enabled>>().unwrap().0 = true;
+        app.update();
+        assert_eq!(app.world.borrow::<UniqueView<Runs>>().unwrap().0, 2);
+    }
+
+    #[test]
+    fn test_condition_missing_unique_skips_system() {
+        let mut app = App::new();
+        app.add_plugin_workload(MissingFlagPlugin);
+
+        app.update();
+        assert_eq!(app.world.borrow::<UniqueView<Runs>>().unwrap().0, 0);
+    }
+
+    #[test]
+    fn test_unique_flag_condition() {
+        let mut app = App::new();
+        let (_, info) = app.add_plugin_workload_with_info(FlagPlugin);
+        assert!(info.systems()[0]
+            .run_condition
+            .expect("run condition recorded")
+            .contains("unique_flag_set"));
+
+        app.update();
+        assert_eq!(app.world.borrow::<UniqueView<Runs>>().unwrap().0, 0);
+
+        app.world.borrow::<UniqueViewMut<Enabled>>().unwrap().0 = true;
+        app.update();
+        app.update();
+        assert_eq!(app.world.borrow::<UniqueView<Runs>>().unwrap().0, 2);
+    }
+
+    #[test]
+    fn test_storage_condition() {
+        let mut app = App::new();
+        app.add_plugin_workload(StoragePlugin);
+
+        app.update();
+        assert_eq!(app.world.borrow::<UniqueView<Runs>>().unwrap().0, 0);
+
+        app.world.add_entity((A,));
+        app.update();
+        assert_eq!(app.world.borrow::<UniqueView<Runs>>().unwrap().0, 1);
+
+        // update_pack reset the tracking at the end of the previous update
+        app.update();
+        assert_eq!(app.world.borrow::<UniqueView<Runs>>().unwrap().0, 1);
+    }
+}
+
+#[cfg(test)]
+mod plugin_toggle_tests {
+    use super::*;
+
+    #[derive(Component, Default)]
+    struct Runs(Vec<&'static str>);
+    struct Root;
+    struct Feature;
+    struct FeatureChild;
+
+    fn run_root(mut runs: UniqueViewMut<Runs>) {
+        runs.0.push("root");
+    }
+    fn run_feature(mut runs: UniqueViewMut<Runs>) {
+        runs.0.push("feature");
+    }
+    fn run_feature_child(mut runs: UniqueViewMut<Runs>) {
+        runs.0.push("feature_child");
+    }
+
+    impl Plugin for Root {
+        fn build(&self, app: &mut AppBuilder) {
+            app.add_unique(Runs::default())
+                .add_system(run_root)
+                .add_plugin(Feature);
+        }
+    }
+
+    impl Plugin for Feature {
+        fn build(&self, app: &mut AppBuilder) {
+            app.add_system(run_feature).add_plugin(FeatureChild);
+        }
+        fn can_be_disabled(&self) -> bool {
+            true
+        }
+    }
+
+    impl Plugin for FeatureChild {
+        fn build(&self, app: &mut AppBuilder) {
+            app.add_system(run_feature_child);
+        }
+    }
+
+    fn take_runs(app: &App) -> Vec<&'static str> {
+        std::mem::take(&mut app.world.borrow::<UniqueViewMut<Runs>>().unwrap().0)
+    }
+
+    #[test]
+    fn test_disabled_plugin_systems_are_skipped() {
+        let mut app = App::new();
+        app.add_plugin_workload(Root);
+
+        app.update();
+        assert_eq!(take_runs(&app), vec!["root", "feature", "feature_child"]);
+
+        assert!(app.set_plugin_enabled::<Feature>(false));
+        app.update();
+        assert_eq!(take_runs(&app), vec!["root"]);
+        assert_eq!(
+            app.world
+                .borrow::<UniqueView<PluginToggles>>()
+                .unwrap()
+                .disabled(),
+            vec![type_name::<Feature>()]
+        );
+
+        assert!(app.set_plugin_enabled::<Feature>(true));
+        app.update();
+        assert_eq!(take_runs(&app), vec!["root", "feature", "feature_child"]);
+    }
+
+    #[test]
+    fn test_plugins_must_opt_in_to_toggles() {
+        let mut app = App::new();
+        app.add_plugin_workload(Root);
+
+        assert!(!app.set_plugin_enabled::<FeatureChild>(false));
+        app.update();
+        assert_eq!(take_runs(&app), vec!["root", "feature", "feature_child"]);
+    }
+}
+
+#[cfg(test)]
+mod fixed_timestep_tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[derive(Component, Default)]
+    struct Steps(u32);
+    struct FixedPlugin;
+
+    fn count_steps(mut steps: UniqueViewMut<Steps>) {
+        steps.0 += 1;
+    }
+
+    impl Plugin for FixedPlugin {
+        fn build(&self, app: &mut AppBuilder) {
+            app.add_unique(Steps::default())
+                .set_fixed_timestep(FixedTimestep::new(Duration::from_millis(10)))
+                .add_fixed_system(count_steps);
+        }
+    }
+
+    fn update_after(app: &App, delta: Duration) -> u32 {
+        app.world
+            .borrow::<UniqueViewMut<Time>>()
+            .unwrap()
+            .advance(delta);
+        app.update();
+        app.world.borrow::<UniqueView<Steps>>().unwrap().0
+    }
+
+    #[test]
+    fn test_fixed_systems_run_per_accumulated_step() {
+        let mut app = App::new();
+        let (_, info) = app.add_plugin_workload_with_info(FixedPlugin);
+        assert!(info
+            .stages()
+            .iter()
+            .any(|stage| stage.stage == stage::FIXED_UPDATE));
+
+        assert_eq!(update_after(&app, Duration::from_millis(25)), 2);
+        assert_eq!(update_after(&app, Duration::from_millis(4)), 2);
+        assert_eq!(update_after(&app, Duration::from_millis(1)), 3);
+        // catch up is capped at the default 5 steps per update
+        assert_eq!(update_after(&app, Duration::from_secs(1)), 8);
+    }
+}
@@ -0,0 +1,133 @@
+use std::borrow::Cow;
+
+use shipyard::error;
+
+use super::{PluginAssociated, PluginId, SystemProvenance, UnmetUniqueDependency};
+
+/// Reasons an [AppBuilder](crate::AppBuilder) could not produce a workload.
+///
+/// Returned by [AppBuilder::try_finish](crate::AppBuilder::try_finish) and
+/// [App::try_add_plugin_workload](crate::App::try_add_plugin_workload) so that plugin misconfiguration
+/// can be reported without panicking.
+#[derive(Debug)]
+pub enum BuildError {
+    /// A plugin was added more than once without overriding [Plugin::can_add_multiple_times](crate::Plugin::can_add_multiple_times).
+    DuplicatePlugin {
+        plugin: &'static str,
+        /// Where the plugin was added the first time
+        existing: PluginId,
+        /// The plugin attempting to add it again
+        added_by: PluginId,
+    },
+    /// A plugin (indirectly) attempted to add itself.
+    PluginCycle {
+        plugin: &'static str,
+        added_by: PluginId,
+    },
+    /// A plugin declared a dependency on a plugin which was never added.
+    MissingPluginDependency {
+        dependency: &'static str,
+        dependent: PluginAssociated,
+    },
+    /// A unique declared with [AppBuilder::depends_on_unique](crate::AppBuilder::depends_on_unique) was not provided.
+    MissingUnique(UnmetUniqueDependency),
+    /// A system or stage referred to a stage which was never added.
+    UnknownStage {
+        stage: &'static str,
+        plugin: PluginId,
+    },
+    /// A system was ordered before or after a label which no system of its stage has.
+    UnknownSystemLabel {
+        label: &'static str,
+        stage: &'static str,
+        system: &'static str,
+        plugin: PluginId,
+    },
+    /// The `before`/`after` constraints of these systems form a cycle.
+    SystemOrderCycle {
+        stage: &'static str,
+        systems: Vec<SystemProvenance>,
+    },
+    /// A system could not be converted into a workload system.
+    InvalidSystem {
+        system: &'static str,
+        plugin: PluginId,
+        error: error::InvalidSystem,
+    },
+    /// Shipyard refused to add the finished workload to the world.
+    WorkloadRegistration {
+        workload: Cow<'static, str>,
+        error: error::AddWorkload,
+    },
+}
+
+impl std::fmt::Display for BuildError {
+    fn fmt(&self, mut f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BuildError::DuplicatePlugin {
+                plugin,
+                existing,
+                added_by,
+            } => write!(
+                &mut f,
+                "Plugin ({}) cannot add plugin ({}) as it's already added as \"{}\". (Implement `Plugin::can_add_multiple_times` to override)",
+                added_by, plugin, existing
+            ),
+            BuildError::PluginCycle { plugin, added_by } => write!(
+                &mut f,
+                "Plugin ({}) cannot add plugin ({}) as it would cause a cycle",
+                added_by, plugin
+            ),
+            BuildError::MissingPluginDependency {
+                dependency,
+                dependent,
+            } => write!(
+                &mut f,
+                "\"{}\" depends on \"{}\": {}",
+                dependent.plugin, dependency, dependent.reason
+            ),
+            BuildError::MissingUnique(unmet) => std::fmt::Display::fmt(unmet, f),
+            BuildError::UnknownStage { stage, plugin } => write!(
+                &mut f,
+                "Plugin ({}) refers to unknown stage \"{}\"",
+                plugin, stage
+            ),
+            BuildError::UnknownSystemLabel {
+                label,
+                stage,
+                system,
+                plugin,
+            } => write!(
+                &mut f,
+                "Plugin ({}) ordered system ({}) relative to label \"{}\", but no system in stage \"{}\" has that label",
+                plugin, system, label, stage
+            ),
+            BuildError::SystemOrderCycle { stage, systems } => write!(
+                &mut f,
+                "Systems in stage \"{}\" have cyclic ordering constraints:\n{}",
+                stage,
+                systems
+                    .iter()
+                    .map(|system| format!("  {} (labels: {:?})", system, system.labels))
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            ),
+            BuildError::InvalidSystem {
+                system,
+                plugin,
+                error,
+            } => write!(
+                &mut f,
+                "Plugin ({}) added invalid system ({}): {:?}",
+                plugin, system, error
+            ),
+            BuildError::WorkloadRegistration { workload, error } => write!(
+                &mut f,
+                "Workload ({}) could not be added to the world: {:?}",
+                workload, error
+            ),
+        }
+    }
+}
+
+impl std::error::Error for BuildError {}
@@ -0,0 +1,157 @@
+use std::{any::TypeId, collections::HashMap};
+
+use super::{PluginId, WorkloadSignature};
+
+/// How two plugins in a [PluginGraph] are related
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PluginEdgeKind {
+    /// The plugin added the other plugin with [AppBuilder::add_plugin](crate::AppBuilder::add_plugin)
+    Adds,
+    /// The plugin declared [AppBuilder::depends_on_plugin](crate::AppBuilder::depends_on_plugin) on the other plugin
+    DependsOn,
+    /// The plugin declared [AppBuilder::depends_on_default_plugin](crate::AppBuilder::depends_on_default_plugin) on the other plugin
+    DependsOnDefault,
+}
+
+impl PluginEdgeKind {
+    fn is_dependency(self) -> bool {
+        matches!(
+            self,
+            PluginEdgeKind::DependsOn | PluginEdgeKind::DependsOnDefault
+        )
+    }
+}
+
+/// A plugin added to the workload
+#[derive(Clone, Debug)]
+pub struct PluginNode {
+    pub type_id: TypeId,
+    pub name: &'static str,
+    /// Where the plugin was added in the plugin nesting
+    pub plugin: PluginId,
+}
+
+/// A relationship from one plugin to another
+#[derive(Clone, Debug)]
+pub struct PluginEdge {
+    pub from: TypeId,
+    pub from_name: &'static str,
+    pub to: TypeId,
+    pub to_name: &'static str,
+    pub kind: PluginEdgeKind,
+    pub reason: &'static str,
+}
+
+/// Plugins of a workload and how they pulled each other in.
+///
+/// Retrieve with [AppWorkloadInfo::plugin_graph](crate::AppWorkloadInfo::plugin_graph).
+#[derive(Clone, Debug, Default)]
+pub struct PluginGraph {
+    nodes: Vec<PluginNode>,
+    edges: Vec<PluginEdge>,
+}
+
+impl PluginGraph {
+    pub(crate) fn new(
+        added_plugins: &HashMap<TypeId, PluginId>,
+        signature: &WorkloadSignature,
+    ) -> Self {
+        let mut nodes = added_plugins
+            .iter()
+            .filter_map(|(type_id, plugin)| {
+                plugin.last().map(|(_, name)| PluginNode {
+                    type_id: *type_id,
+                    name,
+                    plugin: plugin.clone(),
+                })
+            })
+            .collect::<Vec<_>>();
+        nodes.sort_by_key(|node| node.name);
+
+        let mut edges = Vec::new();
+        for (map, kind) in [
+            (&signature.track_plugins_added, PluginEdgeKind::Adds),
+            (
+                &signature.track_plugin_dependencies,
+                PluginEdgeKind::DependsOn,
+            ),
+            (
+                &signature.track_plugin_default_dependencies,
+                PluginEdgeKind::DependsOnDefault,
+            ),
+        ]
+        .iter()
+        {
+            for ((to, to_name), associations) in map.entries() {
+                for association in associations {
+                    if let Some((from, from_name)) = association.plugin.last() {
+                        edges.push(PluginEdge {
+                            from,
+                            from_name,
+                            to,
+                            to_name,
+                            kind: *kind,
+                            reason: association.reason,
+                        });
+                    }
+                }
+            }
+        }
+
+        PluginGraph { nodes, edges }
+    }
+
+    /// Every plugin added to the workload, sorted by name
+    pub fn nodes(&self) -> &[PluginNode] {
+        &self.nodes
+    }
+
+    /// Every "adds", "depends on" and "depends on default" relationship between plugins
+    pub fn edges(&self) -> &[PluginEdge] {
+        &self.edges
+    }
+
+    pub fn contains<P: 'static>(&self) -> bool {
+        self.node(TypeId::of::<P>()).is_some()
+    }
+
+    pub fn node(&self, type_id: TypeId) -> Option<&PluginNode> {
+        self.nodes.iter().find(|node| node.type_id == type_id)
+    }
+
+    /// Relationships starting at plugin `P`
+    pub fn edges_from<P: 'static>(&self) -> impl Iterator<Item = &PluginEdge> {
+        let type_id = TypeId::of::<P>();
+        self.edges.iter().filter(move |edge| edge.from == type_id)
+    }
+
+    /// Relationships ending at plugin `P`
+    pub fn edges_to<P: 'static>(&self) -> impl Iterator<Item = &PluginEdge> {
+        let type_id = TypeId::of::<P>();
+        self.edges.iter().filter(move |edge| edge.to == type_id)
+    }
+
+    /// Plugins directly added by plugin `P`
+    pub fn added_by<P: 'static>(&self) -> Vec<&'static str> {
+        self.edges_from::<P>()
+            .filter(|edge| edge.kind == PluginEdgeKind::Adds)
+            .map(|edge| edge.to_name)
+            .collect()
+    }
+
+    /// Plugins which plugin `P` declared a dependency on
+    pub fn dependencies_of<P: 'static>(&self) -> Vec<&'static str> {
+        self.edges_from::<P>()
+            .filter(|edge| edge.kind.is_dependency())
+            .map(|edge| edge.to_name)
+            .collect()
+    }
+
+    /// Plugins which declared a dependency on plugin `P`
+    pub fn dependents_of<P: 'static>(&self) -> Vec<&'static str> {
+        self.edges_to::<P>()
+            .filter(|edge| edge.kind.is_dependency())
+            .map(|edge| edge.from_name)
+            .collect()
+    }
+}
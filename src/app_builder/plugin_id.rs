@@ -14,6 +14,17 @@ impl PluginId {
     pub(crate) fn pop(&mut self) {
         self.0.pop();
     }
+    pub(crate) fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+    /// Plugins from outermost to innermost
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (TypeId, &'static str)> + '_ {
+        self.0.iter().copied()
+    }
+    /// The innermost plugin
+    pub(crate) fn last(&self) -> Option<(TypeId, &'static str)> {
+        self.0.last().copied()
+    }
 }
 
 impl std::fmt::Debug for PluginId {
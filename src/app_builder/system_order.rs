@@ -0,0 +1,215 @@
+use std::{
+    any::TypeId,
+    collections::HashSet,
+    ops::{Deref, DerefMut},
+};
+
+use shipyard::{IntoWorkloadSystem, System, WorkloadSystem};
+
+use super::{AppBuilder, BuildError, SystemProvenance};
+use crate::run_condition::{RunCondition, SystemFactory};
+
+/// Labels a system must run before or after within its stage
+#[derive(Clone, Debug, Default)]
+pub(crate) struct SystemOrdering {
+    pub before: Vec<&'static str>,
+    pub after: Vec<&'static str>,
+}
+
+/// Systems which can be added to an [AppBuilder].
+///
+/// They must be [Clone] (as functions and closures capturing [Clone] values are) since a workload with conditional
+/// systems needs a copy of its other systems for each variant skipping some of the conditional ones.
+pub trait AppSystem<B, R>: IntoWorkloadSystem<B, R> + Clone + Send + Sync + 'static {}
+
+impl<B, R, S> AppSystem<B, R> for S where S: IntoWorkloadSystem<B, R> + Clone + Send + Sync + 'static
+{}
+
+/// A system waiting for the builder to finish
+pub(crate) struct PendingSystem {
+    pub system: WorkloadSystem,
+    /// Another copy of the system, for the variants of its workload which skip conditional systems
+    pub rebuild: SystemFactory,
+    pub condition: Option<RunCondition>,
+    /// Plugins in the system's [PluginId](super::PluginId) which can be disabled
+    pub toggled_by: Vec<(TypeId, &'static str)>,
+}
+
+/// Returned by [AppBuilder::add_system_labeled] to declare ordering constraints on the system just added.
+///
+/// Dereferences to the [AppBuilder] so adding systems and plugins can continue to be chained.
+///
+/// A system constrained to run after another is placed in a later workload of the stage, so the constraint holds
+/// even between systems without conflicting borrows. Each of these workloads is a barrier: no system of the stage
+/// starts running before all systems of the previous workload finished. Conditional systems are not barriers.
+pub struct SystemConfig<'b, 'a> {
+    pub(crate) builder: &'b mut AppBuilder<'a>,
+    /// `None` if the system was invalid (the error is already recorded)
+    pub(crate) index: Option<usize>,
+}
+
+impl<'b, 'a> SystemConfig<'b, 'a> {
+    /// Add another label to the system
+    pub fn label(&mut self, label: &'static str) -> &mut Self {
+        if let Some(index) = self.index {
+            self.builder.systems[index].1.labels.push(label);
+        }
+
+        self
+    }
+
+    /// Run the system before every system labeled `label` in the same stage
+    pub fn before(&mut self, label: &'static str) -> &mut Self {
+        if let Some(index) = self.index {
+            self.builder.systems[index].2.before.push(label);
+        }
+
+        self
+    }
+
+    /// Run the system after every system labeled `label` in the same stage
+    pub fn after(&mut self, label: &'static str) -> &mut Self {
+        if let Some(index) = self.index {
+            self.builder.systems[index].2.after.push(label);
+        }
+
+        self
+    }
+
+    /// Skip the system whenever `condition` returns `false`, see [AppBuilder::add_system_if]
+    pub fn run_if<B: 'static, C>(&mut self, condition: C) -> &mut Self
+    where
+        C: for<'s> System<'s, (), B, bool> + Clone + Send + Sync + 'static,
+    {
+        if let Some(index) = self.index {
+            let condition = RunCondition::new(condition);
+            self.builder.systems[index].1.run_condition = Some(condition.name);
+            self.builder.systems[index].0.condition = Some(condition);
+        }
+
+        self
+    }
+}
+
+impl<'b, 'a> Deref for SystemConfig<'b, 'a> {
+    type Target = AppBuilder<'a>;
+
+    fn deref(&self) -> &Self::Target {
+        self.builder
+    }
+}
+
+impl<'b, 'a> DerefMut for SystemConfig<'b, 'a> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.builder
+    }
+}
+
+/// Sort the systems of a stage so every `before`/`after` constraint is met, grouped so no system is in the same
+/// group as a system it must run after.
+///
+/// Systems without constraints between them keep the order they were added in.
+pub(crate) fn order_stage_systems<T>(
+    stage: &'static str,
+    systems: Vec<(T, SystemProvenance, SystemOrdering)>,
+) -> Result<Vec<Vec<(T, SystemProvenance)>>, Vec<BuildError>> {
+    let mut errors = Vec::new();
+    let mut runs_before = vec![HashSet::<usize>::new(); systems.len()];
+
+    for (index, (_, provenance, ordering)) in systems.iter().enumerate() {
+        let constraints = ordering
+            .before
+            .iter()
+            .map(|label| (*label, true))
+            .chain(ordering.after.iter().map(|label| (*label, false)));
+        for (label, is_before) in constraints {
+            let labeled = systems
+                .iter()
+                .enumerate()
+                .filter(|(_, (_, other, _))| other.labels.contains(&label))
+                .map(|(other_index, _)| other_index)
+                .collect::<Vec<_>>();
+            if labeled.is_empty() {
+                errors.push(BuildError::UnknownSystemLabel {
+                    label,
+                    stage,
+                    system: provenance.system,
+                    plugin: provenance.plugin.clone(),
+                });
+            }
+
+            for other_index in labeled.into_iter().filter(|other| *other != index) {
+                if is_before {
+                    runs_before[index].insert(other_index);
+                } else {
+                    runs_before[other_index].insert(index);
+                }
+            }
+        }
+    }
+
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+
+    let mut remaining_dependencies = vec![0usize; systems.len()];
+    for successors in runs_before.iter() {
+        for successor in successors {
+            remaining_dependencies[*successor] += 1;
+        }
+    }
+
+    // always take the earliest added system which is ready, so unconstrained systems keep their order
+    let mut order = Vec::with_capacity(systems.len());
+    let mut placed = vec![false; systems.len()];
+    while let Some(next) =
+        (0..systems.len()).find(|index| !placed[*index] && remaining_dependencies[*index] == 0)
+    {
+        placed[next] = true;
+        order.push(next);
+        for successor in runs_before[next].iter() {
+            remaining_dependencies[*successor] -= 1;
+        }
+    }
+
+    if order.len() < systems.len() {
+        return Err(vec![BuildError::SystemOrderCycle {
+            stage,
+            systems: systems
+                .iter()
+                .enumerate()
+                .filter(|(index, _)| !placed[*index])
+                .map(|(_, (_, provenance, _))| provenance.clone())
+                .collect(),
+        }]);
+    }
+
+    // start a new group whenever a system must run after a system of the current group
+    let mut group_of = vec![0usize; systems.len()];
+    let mut group = 0;
+    for (position, index) in order.iter().enumerate() {
+        let runs_after_current_group = order[..position]
+            .iter()
+            .any(|other| group_of[*other] == group && runs_before[*other].contains(index));
+        if runs_after_current_group {
+            group += 1;
+        }
+        group_of[*index] = group;
+    }
+
+    let mut systems = systems
+        .into_iter()
+        .map(|(system, provenance, _)| Some((system, provenance)))
+        .collect::<Vec<_>>();
+    let mut groups: Vec<Vec<(T, SystemProvenance)>> = Vec::new();
+    for index in order {
+        if groups.len() <= group_of[index] {
+            groups.push(Vec::new());
+        }
+        if let Some(system) = systems[index].take() {
+            groups[group_of[index]].push(system);
+        }
+    }
+
+    Ok(groups)
+}
@@ -0,0 +1,38 @@
+use std::panic::Location;
+
+use super::PluginId;
+
+/// Whether the system runs with the rest of the update or among the absolute last systems
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SystemKind {
+    Update,
+    Reset,
+}
+
+/// Where a system of a workload came from, so borrow conflicts and slow batches can be attributed to a plugin.
+#[derive(Clone, Debug)]
+pub struct SystemProvenance {
+    /// Type name of the system (matches [shipyard::info::SystemInfo]'s name)
+    pub system: &'static str,
+    /// The plugins which added the system (innermost last)
+    pub plugin: PluginId,
+    /// Where the system was added
+    pub location: &'static Location<'static>,
+    /// The [stage](crate::stage) the system runs in
+    pub stage: &'static str,
+    /// Labels other systems can be ordered against with [SystemConfig::before](crate::SystemConfig::before) and [SystemConfig::after](crate::SystemConfig::after)
+    pub labels: Vec<&'static str>,
+    /// Type name of the condition added with [AppBuilder::add_system_if](crate::AppBuilder::add_system_if)
+    pub run_condition: Option<&'static str>,
+    pub kind: SystemKind,
+}
+
+impl std::fmt::Display for SystemProvenance {
+    fn fmt(&self, mut f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            &mut f,
+            "{} added by ({}) at {}",
+            self.system, self.plugin, self.location
+        )
+    }
+}
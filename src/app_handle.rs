@@ -0,0 +1,167 @@
+//! Inject work into an [App] from other threads, see [AppHandle].
+use std::{
+    any::type_name,
+    sync::{
+        mpsc::{channel, Receiver, Sender},
+        Mutex,
+    },
+};
+
+use shipyard::*;
+use tracing::{trace_span, warn};
+
+use crate::{stage, AppBuilder, Commands, Events, Plugin};
+
+type Injected = Box<dyn FnOnce(&mut AllStorages) + Send>;
+
+/// Label of the system draining [AppHandle]s in [stage::FIRST]
+pub const APP_HANDLE_LABEL: &str = "app_handle";
+
+/// Both ends of the channel behind [AppHandle]s
+#[derive(Component)]
+pub struct AppHandleChannel {
+    sender: Mutex<Sender<Injected>>,
+    receiver: Mutex<Receiver<Injected>>,
+}
+
+impl Default for AppHandleChannel {
+    fn default() -> Self {
+        let (sender, receiver) = channel();
+        AppHandleChannel {
+            sender: Mutex::new(sender),
+            receiver: Mutex::new(receiver),
+        }
+    }
+}
+
+impl AppHandleChannel {
+    pub(crate) fn handle(&self) -> AppHandle {
+        AppHandle {
+            sender: self.sender.lock().unwrap().clone(),
+        }
+    }
+}
+
+/// Cloneable, [Send] handle to queue work for an [App](crate::App) from other threads.
+///
+/// Queued work is moved to the [CommandQueue](crate::CommandQueue) by the [AppHandlePlugin] at the start of the next update,
+/// and applied in order with the other commands once that update's workload finishes.
+/// Retrieve one with [App::handle](crate::App::handle).
+#[derive(Clone)]
+pub struct AppHandle {
+    sender: Sender<Injected>,
+}
+
+impl AppHandle {
+    /// Queue a closure with exclusive access to the world's storages.
+    ///
+    /// Returns `false` if the app was dropped.
+    pub fn run<F: FnOnce(&mut AllStorages) + Send + 'static>(&self, f: F) -> bool {
+        self.sender.send(Box::new(f)).is_ok()
+    }
+
+    /// Queue a message into [Events] of `M`, which must be added with [AppBuilder::add_event].
+    ///
+    /// Like any other event, it is readable by [EventReader](crate::EventReader)s once the events are swapped.
+    ///
+    /// Returns `false` if the app was dropped. `true` only means the message was queued,
+    /// it is dropped with a warning when applied if [Events] of `M` is missing.
+    pub fn send<M: Send + Sync + 'static>(&self, message: M) -> bool {
+        self.run(
+            move |all_storages| match all_storages.borrow::<UniqueViewMut<Events<M>>>() {
+                Ok(mut events) => events.send(message),
+                Err(_) => warn!(
+                    "AppHandle message dropped, add_event::<{}>() was never called.",
+                    type_name::<M>()
+                ),
+            },
+        )
+    }
+}
+
+/// Moves the work queued through [AppHandle]s to the [CommandQueue](crate::CommandQueue) in [stage::FIRST] of every update
+#[derive(Default)]
+pub struct AppHandlePlugin;
+
+impl Plugin for AppHandlePlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        if app
+            .app
+            .world
+            .borrow::<UniqueView<AppHandleChannel>>()
+            .is_err()
+        {
+            app.add_unique(AppHandleChannel::default());
+        }
+
+        app.add_system_to_stage_labeled(stage::FIRST, &[APP_HANDLE_LABEL], drain_app_handles);
+    }
+}
+
+fn drain_app_handles(uv_channel: UniqueView<AppHandleChannel>, commands: Commands) {
+    let injected = uv_channel
+        .receiver
+        .lock()
+        .unwrap()
+        .try_iter()
+        .collect::<Vec<_>>();
+    if injected.is_empty() {
+        return;
+    }
+
+    let span = trace_span!("drain_app_handles", count = injected.len());
+    let _span = span.enter();
+    for f in injected {
+        commands.add(f);
+    }
+}
+
+#[cfg(test)]
+mod app_handle_tests {
+    use crate::prelude::*;
+
+    #[derive(Component, Default)]
+    struct Total(u32);
+    struct Add(u32);
+    struct HandlePlugin;
+
+    fn sum_messages(adds: EventReader<Add>, mut total: UniqueViewMut<Total>) {
+        total.0 += adds.iter().map(|add| add.0).sum::<u32>();
+    }
+
+    impl Plugin for HandlePlugin {
+        fn build(&self, app: &mut AppBuilder) {
+            app.add_plugin(AppHandlePlugin)
+                .add_unique(Total::default())
+                .add_event::<Add>()
+                .add_system(sum_messages);
+        }
+    }
+
+    fn total(app: &App) -> u32 {
+        app.world.borrow::<UniqueView<Total>>().unwrap().0
+    }
+
+    #[test]
+    fn test_handles_from_other_threads() {
+        let mut app = App::new();
+        app.add_plugin_workload(HandlePlugin);
+
+        let handle = app.handle();
+        std::thread::spawn(move || {
+            handle.send(Add(1));
+            handle.send(Add(2));
+            handle.run(|all_storages| {
+                all_storages.borrow::<UniqueViewMut<Total>>().unwrap().0 += 10;
+            });
+        })
+        .join()
+        .unwrap();
+
+        // closures apply after the update's workload, messages are readable after the next events swap
+        app.update();
+        assert_eq!(total(&app), 10);
+        app.update();
+        assert_eq!(total(&app), 13);
+    }
+}
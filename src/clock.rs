@@ -0,0 +1,59 @@
+//! Sources of time which can be swapped out, so runners and time keeping can be driven manually in tests.
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+/// A monotonic clock which can also wait
+pub trait Clock: Send + Sync {
+    /// Time elapsed since the clock started
+    fn now(&self) -> Duration;
+    /// Block until `duration` has passed
+    fn sleep(&self, duration: Duration);
+}
+
+/// [Clock] backed by [Instant] and [std::thread::sleep]
+#[derive(Clone, Debug)]
+pub struct SystemClock {
+    start: Instant,
+}
+
+impl Default for SystemClock {
+    fn default() -> Self {
+        SystemClock {
+            start: Instant::now(),
+        }
+    }
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> Duration {
+        self.start.elapsed()
+    }
+
+    fn sleep(&self, duration: Duration) {
+        std::thread::sleep(duration);
+    }
+}
+
+/// [Clock] which only moves when advanced (or slept on), clones share the same time.
+#[derive(Clone, Debug, Default)]
+pub struct ManualClock {
+    now: Arc<Mutex<Duration>>,
+}
+
+impl ManualClock {
+    pub fn advance(&self, duration: Duration) {
+        *self.now.lock().unwrap() += duration;
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Duration {
+        *self.now.lock().unwrap()
+    }
+
+    fn sleep(&self, duration: Duration) {
+        self.advance(duration);
+    }
+}
@@ -0,0 +1,215 @@
+//! Structural changes requested by systems and applied between the workloads of an [AppWorkload](crate::AppWorkload).
+use std::sync::Mutex;
+
+use shipyard::*;
+use tracing::trace_span;
+
+type Command = Box<dyn FnOnce(&mut AllStorages) + Send>;
+
+/// Commands recorded through [Commands] views, waiting to be applied.
+///
+/// Added to the world when the first [AppWorkload](crate::AppWorkload) of an [App](crate::App) is finished.
+#[derive(Component, Default)]
+pub struct CommandQueue(Mutex<Vec<Command>>);
+
+impl CommandQueue {
+    fn push(&self, command: Command) {
+        self.0.lock().unwrap().push(command);
+    }
+
+    fn take(&self) -> Vec<Command> {
+        std::mem::take(&mut *self.0.lock().unwrap())
+    }
+}
+
+/// Apply every recorded command, called by [AppWorkload::run](crate::AppWorkload::run) after each of its workloads,
+/// by [App::update](crate::App::update) after the world's default workload when no [AppWorkload](crate::AppWorkload) was finished,
+/// and by [App::run_and_apply](crate::App::run_and_apply) after its system
+pub(crate) fn apply_commands(world: &World) {
+    let commands = match world.borrow::<UniqueView<CommandQueue>>() {
+        Ok(queue) => queue.take(),
+        Err(_) => return,
+    };
+    if commands.is_empty() {
+        return;
+    }
+
+    let span = trace_span!("apply_commands", count = commands.len());
+    let _span = span.enter();
+    let mut all_storages = world.borrow::<AllStoragesViewMut>().unwrap();
+    for command in commands {
+        command(&mut all_storages);
+    }
+}
+
+/// A shipyard view to request structural changes without borrowing [AllStoragesViewMut] or [EntitiesViewMut].
+///
+/// Only the [CommandQueue] unique is borrowed (shared), so systems recording commands can still run in parallel.
+/// Commands are applied in the order they were recorded once the current workload finishes, when run through
+/// [App::update](crate::App::update), an [AppWorkload](crate::AppWorkload) or [App::run_and_apply](crate::App::run_and_apply).
+/// Systems run otherwise (such as with [App::run](crate::App::run) or [World::run]) leave their commands
+/// queued until the next time the app applies them.
+///
+/// ```
+/// use shipyard_app::prelude::*;
+///
+/// #[derive(Component)]
+/// struct Health(u32);
+///
+/// fn despawn_dead(v_health: View<Health>, commands: Commands) {
+///     for (entity, health) in v_health.iter().with_id() {
+///         if health.0 == 0 {
+///             commands.despawn(entity);
+///         }
+///     }
+/// }
+/// ```
+pub struct Commands<'a>(UniqueView<'a, CommandQueue>);
+
+impl Commands<'_> {
+    /// Add a new entity with `components`
+    pub fn spawn<C: AddEntity + Send + 'static>(&self, components: C) {
+        self.add(move |all_storages| {
+            all_storages.add_entity(components);
+        });
+    }
+
+    /// Delete `entity` and all of its components
+    pub fn despawn(&self, entity: EntityId) {
+        self.add(move |all_storages| {
+            all_storages.delete_entity(entity);
+        });
+    }
+
+    /// Add `components` to `entity`, if it's still alive when the command is applied
+    pub fn add_component<C: TupleAddComponent + Send + 'static>(
+        &self,
+        entity: EntityId,
+        components: C,
+    ) {
+        self.add(move |all_storages| {
+            all_storages.add_component(entity, components);
+        });
+    }
+
+    /// Remove the components `C` from `entity`
+    pub fn remove<C: TupleRemove + Send + 'static>(&self, entity: EntityId) {
+        self.add(move |all_storages| {
+            all_storages.remove::<C>(entity);
+        });
+    }
+
+    /// Record any other structural change
+    pub fn add<F: FnOnce(&mut AllStorages) + Send + 'static>(&self, command: F) {
+        self.0.push(Box::new(command));
+    }
+}
+
+pub struct CommandsBorrower;
+
+impl IntoBorrow for Commands<'_> {
+    type Borrow = CommandsBorrower;
+}
+
+impl<'a> Borrow<'a> for CommandsBorrower {
+    type View = Commands<'a>;
+
+    fn borrow(
+        world: &'a World,
+        last_run: Option<u32>,
+        current: u32,
+    ) -> Result<Self::View, error::GetStorage> {
+        Ok(Commands(
+            <UniqueView<CommandQueue> as IntoBorrow>::Borrow::borrow(world, last_run, current)?,
+        ))
+    }
+}
+
+unsafe impl<'a> BorrowInfo for Commands<'a> {
+    fn borrow_info(mut info: &mut Vec<info::TypeInfo>) {
+        UniqueView::<'a, CommandQueue>::borrow_info(&mut info);
+    }
+}
+
+#[cfg(test)]
+mod commands_tests {
+    use crate::{prelude::*, CommandQueue};
+
+    #[derive(Component)]
+    struct Spawner;
+    #[derive(Component, Debug, PartialEq)]
+    struct Spawned(u32);
+    struct CommandsPlugin;
+
+    fn spawn_and_despawn(v_spawner: View<Spawner>, v_spawned: View<Spawned>, commands: Commands) {
+        for entity in v_spawner.iter().ids() {
+            commands.spawn((Spawned(1),));
+            commands.remove::<(Spawner,)>(entity);
+        }
+        for (entity, spawned) in v_spawned.iter().with_id() {
+            if spawned.0 == 1 {
+                commands.add_component(entity, (Spawned(2),));
+            } else {
+                commands.despawn(entity);
+            }
+        }
+    }
+
+    impl Plugin for CommandsPlugin {
+        fn build(&self, app: &mut AppBuilder) {
+            app.add_system(spawn_and_despawn);
+        }
+    }
+
+    fn spawned(app: &App) -> Vec<u32> {
+        app.run(|v_spawned: View<Spawned>| v_spawned.iter().map(|spawned| spawned.0).collect())
+    }
+
+    #[test]
+    fn test_commands_apply_after_workload() {
+        let mut app = App::new();
+        app.add_plugin_workload(CommandsPlugin);
+        app.world.add_entity((Spawner,));
+
+        app.update();
+        assert_eq!(spawned(&app), vec![1]);
+        assert_eq!(app.run(|v_spawner: View<Spawner>| v_spawner.len()), 0);
+
+        app.update();
+        assert_eq!(spawned(&app), vec![2]);
+
+        app.update();
+        assert_eq!(spawned(&app), Vec::<u32>::new());
+    }
+
+    #[test]
+    fn test_commands_apply_after_run_and_apply() {
+        let app = App::new();
+        app.world.add_unique(CommandQueue::default()).unwrap();
+        app.world.add_entity((Spawner,));
+
+        app.run_and_apply(spawn_and_despawn);
+        assert_eq!(spawned(&app), vec![1]);
+
+        // App::run leaves its commands queued until the app applies them
+        app.run(spawn_and_despawn);
+        assert_eq!(spawned(&app), vec![1]);
+        app.run_and_apply(|_commands: Commands| {});
+        assert_eq!(spawned(&app), vec![2]);
+    }
+
+    #[test]
+    fn test_commands_apply_after_default_shipyard_workload() {
+        let app = App::new();
+        // without an AppWorkload, the queue isn't added for us
+        app.world.add_unique(CommandQueue::default()).unwrap();
+        WorkloadBuilder::new("default")
+            .with_system(spawn_and_despawn)
+            .add_to_world(&app.world)
+            .unwrap();
+        app.world.add_entity((Spawner,));
+
+        app.update();
+        assert_eq!(spawned(&app), vec![1]);
+    }
+}
@@ -0,0 +1,253 @@
+//! Typed event channels between plugins, see [AppBuilder::add_event](crate::AppBuilder::add_event).
+use std::{any::type_name, marker::PhantomData};
+
+use shipyard::*;
+use tracing::trace_span;
+
+/// Double-buffered queue of events of type `E`.
+///
+/// [EventWriter]s push into the current buffer while [EventReader]s read the events sent during the previous update.
+/// The buffers are swapped by a reset system at the end of the first workload which called [AppBuilder::add_event](crate::AppBuilder::add_event).
+///
+/// Events become readable at the first swap after they were sent and are dropped at the second one.
+/// So a reader sees every event if it runs once between every two swaps, such as once per cycle, in any workload
+/// and whether it runs before or after the writer. A reader skipped for a whole update (a conditional system,
+/// or a workload not run every cycle) misses the events of that update.
+#[derive(Component)]
+pub struct Events<E: Send + Sync + 'static> {
+    previous: Vec<E>,
+    current: Vec<E>,
+}
+
+impl<E: Send + Sync + 'static> Default for Events<E> {
+    fn default() -> Self {
+        Events {
+            previous: Vec::new(),
+            current: Vec::new(),
+        }
+    }
+}
+
+impl<E: Send + Sync + 'static> Events<E> {
+    pub fn send(&mut self, event: E) {
+        self.current.push(event);
+    }
+
+    /// Events sent during the previous update
+    pub fn iter(&self) -> std::slice::Iter<'_, E> {
+        self.previous.iter()
+    }
+
+    /// Drop the events sent during the previous update and make the current events readable
+    pub fn swap(&mut self) {
+        std::mem::swap(&mut self.previous, &mut self.current);
+        self.current.clear();
+    }
+}
+
+pub(crate) fn swap_events<E: Send + Sync + 'static>(mut uvm_events: UniqueViewMut<Events<E>>) {
+    let span = trace_span!("swap_events", event = ?type_name::<E>());
+    let _span = span.enter();
+    uvm_events.swap();
+}
+
+/// A shipyard view to send events of type `E`, which [EventReader]s receive in the next update.
+///
+/// ```
+/// use shipyard_app::prelude::*;
+///
+/// struct Jumped(EntityId);
+///
+/// fn send_jumps(mut jumps: EventWriter<Jumped>) {
+///     jumps.send(Jumped(EntityId::dead()));
+/// }
+///
+/// fn count_jumps(jumps: EventReader<Jumped>) -> usize {
+///     jumps.iter().count()
+/// }
+/// ```
+pub struct EventWriter<'a, E: Send + Sync + 'static>(UniqueViewMut<'a, Events<E>>);
+
+impl<E: Send + Sync + 'static> EventWriter<'_, E> {
+    pub fn send(&mut self, event: E) {
+        self.0.send(event);
+    }
+
+    pub fn send_batch(&mut self, events: impl IntoIterator<Item = E>) {
+        for event in events {
+            self.0.send(event);
+        }
+    }
+}
+
+/// A shipyard view to read the events of type `E` sent during the previous update.
+pub struct EventReader<'a, E: Send + Sync + 'static>(UniqueView<'a, Events<E>>);
+
+impl<E: Send + Sync + 'static> EventReader<'_, E> {
+    pub fn iter(&self) -> std::slice::Iter<'_, E> {
+        self.0.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.previous.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.previous.is_empty()
+    }
+}
+
+pub struct EventWriterBorrower<E>(PhantomData<E>);
+
+impl<E: Send + Sync + 'static> IntoBorrow for EventWriter<'_, E> {
+    type Borrow = EventWriterBorrower<E>;
+}
+
+impl<'a, E: Send + Sync + 'static> Borrow<'a> for EventWriterBorrower<E> {
+    type View = EventWriter<'a, E>;
+
+    fn borrow(
+        world: &'a World,
+        last_run: Option<u32>,
+        current: u32,
+    ) -> Result<Self::View, error::GetStorage> {
+        Ok(EventWriter(
+            <UniqueViewMut<Events<E>> as IntoBorrow>::Borrow::borrow(world, last_run, current)?,
+        ))
+    }
+}
+
+unsafe impl<'a, E: Send + Sync + 'static> BorrowInfo for EventWriter<'a, E> {
+    fn borrow_info(mut info: &mut Vec<info::TypeInfo>) {
+        UniqueViewMut::<'a, Events<E>>::borrow_info(&mut info);
+    }
+}
+
+pub struct EventReaderBorrower<E>(PhantomData<E>);
+
+impl<E: Send + Sync + 'static> IntoBorrow for EventReader<'_, E> {
+    type Borrow = EventReaderBorrower<E>;
+}
+
+impl<'a, E: Send + Sync + 'static> Borrow<'a> for EventReaderBorrower<E> {
+    type View = EventReader<'a, E>;
+
+    fn borrow(
+        world: &'a World,
+        last_run: Option<u32>,
+        current: u32,
+    ) -> Result<Self::View, error::GetStorage> {
+        Ok(EventReader(
+            <UniqueView<Events<E>> as IntoBorrow>::Borrow::borrow(world, last_run, current)?,
+        ))
+    }
+}
+
+unsafe impl<'a, E: Send + Sync + 'static> BorrowInfo for EventReader<'a, E> {
+    fn borrow_info(mut info: &mut Vec<info::TypeInfo>) {
+        UniqueView::<'a, Events<E>>::borrow_info(&mut info);
+    }
+}
+
+#[cfg(test)]
+mod events_tests {
+    use crate::prelude::*;
+
+    #[derive(Debug, PartialEq)]
+    struct Ping(u32);
+    #[derive(Component, Default)]
+    struct Received(Vec<u32>);
+    struct Producer;
+    struct Consumer;
+    struct OtherProducer;
+
+    fn send_ping(mut pings: EventWriter<Ping>) {
+        pings.send(Ping(1));
+    }
+
+    fn receive_pings(pings: EventReader<Ping>, mut received: UniqueViewMut<Received>) {
+        received.0.extend(pings.iter().map(|ping| ping.0));
+    }
+
+    impl Plugin for Producer {
+        fn build(&self, app: &mut AppBuilder) {
+            app.add_event::<Ping>().add_system(send_ping);
+        }
+    }
+
+    impl Plugin for Consumer {
+        fn build(&self, app: &mut AppBuilder) {
+            app.depends_on_unique::<Events<Ping>>("receives pings")
+                .add_unique(Received::default())
+                .add_system(receive_pings);
+        }
+    }
+
+    impl Plugin for OtherProducer {
+        fn build(&self, app: &mut AppBuilder) {
+            app.add_event::<Ping>();
+        }
+    }
+
+    #[test]
+    fn test_events_cross_workloads() {
+        let mut app = App::new();
+        let producer = app.add_plugin_workload_with_info(Producer);
+        let consumer = app.add_plugin_workload_with_info(Consumer);
+        let (cycle, _) = app
+            .add_cycle(vec![producer, consumer])
+            .expect("single event swap");
+
+        // producer swaps its events at the end of its workload, so the consumer reads them in the same cycle
+        cycle.run(&app);
+        cycle.run(&app);
+        assert_eq!(
+            app.world.borrow::<UniqueView<Received>>().unwrap().0,
+            vec![1, 1]
+        );
+    }
+
+    #[test]
+    fn test_events_read_before_the_producer() {
+        let mut app = App::new();
+        let producer = app.add_plugin_workload_with_info(Producer);
+        let consumer = app.add_plugin_workload_with_info(Consumer);
+        let (cycle, _) = app
+            .add_cycle(vec![consumer, producer])
+            .expect("single event swap");
+
+        // the consumer reads the events swapped at the end of the previous cycle
+        cycle.run(&app);
+        assert!(app
+            .world
+            .borrow::<UniqueView<Received>>()
+            .unwrap()
+            .0
+            .is_empty());
+        cycle.run(&app);
+        cycle.run(&app);
+        assert_eq!(
+            app.world.borrow::<UniqueView<Received>>().unwrap().0,
+            vec![1, 1]
+        );
+    }
+
+    #[test]
+    fn test_events_added_by_another_workload_are_swapped_once() {
+        let mut app = App::new();
+        let producer = app.add_plugin_workload_with_info(Producer);
+        let other_producer = app.add_plugin_workload_with_info(OtherProducer);
+        assert!(other_producer.1.systems().is_empty(), "no swap scheduled");
+        let consumer = app.add_plugin_workload_with_info(Consumer);
+        let (cycle, _) = app
+            .add_cycle(vec![producer, other_producer, consumer])
+            .expect("single event swap");
+
+        cycle.run(&app);
+        cycle.run(&app);
+        assert_eq!(
+            app.world.borrow::<UniqueView<Received>>().unwrap().0,
+            vec![1, 1]
+        );
+    }
+}
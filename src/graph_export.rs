@@ -0,0 +1,472 @@
+//! Render workloads as Graphviz (DOT) or Mermaid graphs for design reviews.
+//!
+//! Graphs include the plugins of each workload (and which plugins added or depend on which), the systems
+//! plugins added, the storages plugins update pack or track, the uniques plugins provide or depend on,
+//! and the systems of each batch with the storages they borrow.
+use std::fmt::Write;
+
+use shipyard::info;
+
+use crate::{
+    app_add_cycle::CycleSummary, AppWorkloadInfo, PluginEdgeKind, PluginGraph, SystemProvenance,
+    WorkloadSignature,
+};
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Dot,
+    Mermaid,
+}
+
+#[derive(Clone, Copy)]
+enum Shape {
+    Plugin,
+    System,
+    Storage,
+}
+
+#[derive(Clone, Copy)]
+enum Line {
+    Solid,
+    Dashed,
+}
+
+struct GraphWriter {
+    format: Format,
+    out: String,
+    depth: usize,
+}
+
+impl GraphWriter {
+    fn new(format: Format, title: &str) -> Self {
+        let mut writer = GraphWriter {
+            format,
+            out: String::new(),
+            depth: 1,
+        };
+        match format {
+            Format::Dot => {
+                writeln!(&mut writer.out, "digraph \"{}\" {{", escape(format, title)).unwrap();
+                writer.line("rankdir=LR;");
+                writer.line("node [fontname=\"Helvetica\"];");
+            }
+            Format::Mermaid => {
+                writeln!(&mut writer.out, "flowchart LR").unwrap();
+            }
+        }
+        writer
+    }
+
+    fn finish(mut self) -> String {
+        if self.format == Format::Dot {
+            self.out.push_str("}\n");
+        }
+        self.out
+    }
+
+    fn line(&mut self, line: &str) {
+        for _ in 0..self.depth {
+            self.out.push_str("    ");
+        }
+        self.out.push_str(line);
+        self.out.push('\n');
+    }
+
+    fn begin_cluster(&mut self, id: &str, label: &str) {
+        let line = match self.format {
+            Format::Dot => format!(
+                "subgraph \"cluster_{}\" {{ label=\"{}\";",
+                id,
+                escape(self.format, label)
+            ),
+            Format::Mermaid => format!("subgraph {} [\"{}\"]", id, escape(self.format, label)),
+        };
+        self.line(&line);
+        self.depth += 1;
+    }
+
+    fn end_cluster(&mut self) {
+        self.depth -= 1;
+        match self.format {
+            Format::Dot => self.line("}"),
+            Format::Mermaid => self.line("end"),
+        }
+    }
+
+    fn node(&mut self, id: &str, label: &str, shape: Shape) {
+        let label = escape(self.format, label);
+        let line = match (self.format, shape) {
+            (Format::Dot, Shape::Plugin) => format!("{} [label=\"{}\", shape=box];", id, label),
+            (Format::Dot, Shape::System) => format!("{} [label=\"{}\", shape=ellipse];", id, label),
+            (Format::Dot, Shape::Storage) => {
+                format!("{} [label=\"{}\", shape=cylinder];", id, label)
+            }
+            (Format::Mermaid, Shape::Plugin) => format!("{}[\"{}\"]", id, label),
+            (Format::Mermaid, Shape::System) => format!("{}([\"{}\"])", id, label),
+            (Format::Mermaid, Shape::Storage) => format!("{}[(\"{}\")]", id, label),
+        };
+        self.line(&line);
+    }
+
+    fn edge(&mut self, from: &str, to: &str, label: &str, line: Line) {
+        let label = escape(self.format, label);
+        let edge = match (self.format, line) {
+            (Format::Dot, Line::Solid) => format!("{} -> {} [label=\"{}\"];", from, to, label),
+            (Format::Dot, Line::Dashed) => {
+                format!("{} -> {} [label=\"{}\", style=dashed];", from, to, label)
+            }
+            (Format::Mermaid, Line::Solid) => format!("{} -->|\"{}\"| {}", from, label, to),
+            (Format::Mermaid, Line::Dashed) => format!("{} -.->|\"{}\"| {}", from, label, to),
+        };
+        self.line(&edge);
+    }
+}
+
+/// Everything about a single workload that ends up in the graph
+pub(crate) struct WorkloadGraph<'a> {
+    pub name: &'a str,
+    pub signature: &'a WorkloadSignature,
+    pub plugin_graph: &'a PluginGraph,
+    pub batch_info: &'a [info::BatchInfo],
+    pub systems: &'a [SystemProvenance],
+}
+
+fn write_workload(writer: &mut GraphWriter, workload: &WorkloadGraph) {
+    let workload_id = id("workload", workload.name);
+    writer.begin_cluster(&workload_id, workload.name);
+
+    for node in workload.plugin_graph.nodes() {
+        writer.node(
+            &plugin_id(workload.name, node.name),
+            &short_name(node.name),
+            Shape::Plugin,
+        );
+    }
+
+    for (batch_index, batch) in workload.batch_info.iter().enumerate() {
+        writer.begin_cluster(
+            &format!("{}_batch_{}", workload_id, batch_index),
+            &format!("batch {}", batch_index),
+        );
+        for system in batch.systems.iter() {
+            writer.node(
+                &system_id(workload.name, system.name),
+                &short_name(system.name),
+                Shape::System,
+            );
+        }
+        writer.end_cluster();
+    }
+
+    writer.end_cluster();
+
+    for edge in workload.plugin_graph.edges() {
+        let (label, line) = match edge.kind {
+            PluginEdgeKind::Adds => ("adds", Line::Solid),
+            PluginEdgeKind::DependsOn | PluginEdgeKind::DependsOnDefault => {
+                (edge.reason, Line::Dashed)
+            }
+        };
+        writer.edge(
+            &plugin_id(workload.name, edge.from_name),
+            &plugin_id(workload.name, edge.to_name),
+            label,
+            line,
+        );
+    }
+
+    for provenance in workload.systems {
+        if let Some((_, plugin_name)) = provenance.plugin.last() {
+            writer.edge(
+                &plugin_id(workload.name, plugin_name),
+                &system_id(workload.name, provenance.system),
+                "system",
+                Line::Solid,
+            );
+        }
+    }
+
+    let signature = workload.signature;
+    for (associations, relation, line) in [
+        (&signature.track_update_packed, "update_pack", Line::Solid),
+        (&signature.track_tracked_uniques, "tracks", Line::Solid),
+        (&signature.track_events, "swaps", Line::Solid),
+        (&signature.track_uniques_provided, "provides", Line::Solid),
+        (
+            &signature.track_tracked_uniques_provided,
+            "provides",
+            Line::Solid,
+        ),
+        (
+            &signature.track_unique_dependencies,
+            "depends on",
+            Line::Dashed,
+        ),
+    ]
+    .iter()
+    {
+        for ((_, storage_name), plugins) in associations.entries() {
+            for associated in plugins {
+                if let Some((_, plugin_name)) = associated.plugin.last() {
+                    writer.edge(
+                        &plugin_id(workload.name, plugin_name),
+                        &id("storage", storage_name),
+                        relation,
+                        *line,
+                    );
+                }
+            }
+        }
+    }
+
+    for batch in workload.batch_info.iter() {
+        for system in batch.systems.iter() {
+            for borrow in system.borrow.iter() {
+                let (relation, line) = match borrow.mutability {
+                    info::Mutability::Exclusive => ("writes", Line::Solid),
+                    info::Mutability::Shared => ("reads", Line::Dashed),
+                };
+                writer.edge(
+                    &system_id(workload.name, system.name),
+                    &id("storage", storage_type(borrow.name)),
+                    relation,
+                    line,
+                );
+            }
+        }
+    }
+}
+
+fn write_storages(writer: &mut GraphWriter, workloads: &[WorkloadGraph]) {
+    let mut storages = Vec::new();
+    for workload in workloads {
+        let signature = workload.signature;
+        for associations in [
+            &signature.track_update_packed,
+            &signature.track_tracked_uniques,
+            &signature.track_events,
+            &signature.track_uniques_provided,
+            &signature.track_tracked_uniques_provided,
+            &signature.track_unique_dependencies,
+        ]
+        .iter()
+        {
+            storages.extend(
+                associations
+                    .entries()
+                    .into_iter()
+                    .map(|((_, name), _)| name),
+            );
+        }
+        for batch in workload.batch_info.iter() {
+            for system in batch.systems.iter() {
+                storages.extend(system.borrow.iter().map(|borrow| storage_type(borrow.name)));
+            }
+        }
+    }
+    storages.sort_unstable();
+    storages.dedup();
+
+    for storage in storages {
+        writer.node(
+            &id("storage", storage),
+            &short_name(storage),
+            Shape::Storage,
+        );
+    }
+}
+
+fn render(format: Format, title: &str, workloads: &[WorkloadGraph]) -> String {
+    let mut writer = GraphWriter::new(format, title);
+    write_storages(&mut writer, workloads);
+    for workload in workloads {
+        write_workload(&mut writer, workload);
+    }
+    writer.finish()
+}
+
+impl AppWorkloadInfo {
+    fn workload_graph(&self) -> WorkloadGraph {
+        WorkloadGraph {
+            name: &self.name,
+            signature: &self.signature,
+            plugin_graph: &self.plugin_graph,
+            batch_info: &self.batch_info,
+            systems: &self.systems,
+        }
+    }
+
+    /// Render this workload's plugins, systems and storages as a Graphviz DOT graph
+    pub fn to_dot(&self) -> String {
+        render(Format::Dot, &self.name, &[self.workload_graph()])
+    }
+
+    /// Render this workload's plugins, systems and storages as a Mermaid flowchart
+    pub fn to_mermaid(&self) -> String {
+        render(Format::Mermaid, &self.name, &[self.workload_graph()])
+    }
+}
+
+impl CycleSummary {
+    /// Render every workload of the cycle as a Graphviz DOT graph.
+    ///
+    /// Storages are shared between workloads, so conflicting workloads point at the same storage.
+    pub fn to_dot(&self) -> String {
+        render(Format::Dot, "cycle", &self.workload_graphs())
+    }
+
+    /// Render every workload of the cycle as a Mermaid flowchart.
+    ///
+    /// Storages are shared between workloads, so conflicting workloads point at the same storage.
+    pub fn to_mermaid(&self) -> String {
+        render(Format::Mermaid, "cycle", &self.workload_graphs())
+    }
+}
+
+fn plugin_id(workload: &str, plugin: &str) -> String {
+    id("plugin", &format!("{}_{}", workload, plugin))
+}
+
+fn system_id(workload: &str, system: &str) -> String {
+    id("system", &format!("{}_{}", workload, system))
+}
+
+/// Identifiers which are valid in both DOT and Mermaid
+fn id(kind: &str, name: &str) -> String {
+    let mut id = String::with_capacity(kind.len() + name.len() + 1);
+    id.push_str(kind);
+    id.push('_');
+    id.extend(
+        name.chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' }),
+    );
+    id
+}
+
+/// Strip module paths from a type name, including its generics (`a::B<c::D>` becomes `B<D>`)
+fn short_name(type_name: &str) -> String {
+    let mut short = String::with_capacity(type_name.len());
+    let mut segment_start = 0;
+    for (index, c) in type_name.char_indices() {
+        if matches!(c, '<' | '>' | ',' | ' ' | '(' | ')' | '[' | ']' | '&' | ';') {
+            short.push_str(last_path_segment(&type_name[segment_start..index]));
+            short.push(c);
+            segment_start = index + c.len_utf8();
+        }
+    }
+    short.push_str(last_path_segment(&type_name[segment_start..]));
+    short
+}
+
+/// Borrows are reported for the storage (`SparseSet<T>` or `Unique<T>`), but plugins declare the component `T`
+fn storage_type(storage_name: &str) -> &str {
+    let (storage, component) = match (storage_name.find('<'), storage_name.rfind('>')) {
+        (Some(open), Some(close)) if open < close => {
+            (&storage_name[..open], &storage_name[open + 1..close])
+        }
+        _ => return storage_name,
+    };
+    if !matches!(last_path_segment(storage), "SparseSet" | "Unique") {
+        return storage_name;
+    }
+
+    // skip any other generic parameters (e.g. tracking)
+    let mut depth = 0;
+    for (index, c) in component.char_indices() {
+        match c {
+            '<' => depth += 1,
+            '>' => depth -= 1,
+            ',' if depth == 0 => return &component[..index],
+            _ => {}
+        }
+    }
+    component
+}
+
+fn last_path_segment(path: &str) -> &str {
+    path.rsplit("::").next().unwrap_or(path)
+}
+
+fn escape(format: Format, label: &str) -> String {
+    match format {
+        Format::Dot => label.replace('\\', "\\\\").replace('"', "\\\""),
+        Format::Mermaid => label.replace('"', "#quot;"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{App, AppBuilder, Plugin};
+    use shipyard::*;
+
+    #[derive(Component)]
+    #[track(All)]
+    struct A;
+    #[derive(Component)]
+    struct Settings;
+    struct Root;
+    struct Leaf;
+
+    fn read_a(_: View<A>) {}
+
+    impl Plugin for Root {
+        fn build(&self, app: &mut AppBuilder) {
+            app.add_plugin(Leaf)
+                .update_pack::<A>("Root reacts to A")
+                .add_system(read_a);
+        }
+    }
+
+    impl Plugin for Leaf {
+        fn build(&self, app: &mut AppBuilder) {
+            app.add_unique(Settings);
+        }
+    }
+
+    #[test]
+    fn test_short_name() {
+        assert_eq!(short_name("a::b::C"), "C");
+        assert_eq!(short_name("a::B<c::D, e::F>"), "B<D, F>");
+    }
+
+    #[test]
+    fn test_storage_type() {
+        assert_eq!(storage_type("shipyard::SparseSet<a::B>"), "a::B");
+        assert_eq!(
+            storage_type("shipyard::SparseSet<a::B<c::D>, e::All>"),
+            "a::B<c::D>"
+        );
+        assert_eq!(storage_type("shipyard::Unique<a::B>"), "a::B");
+        assert_eq!(storage_type("shipyard::Entities"), "shipyard::Entities");
+    }
+
+    #[test]
+    fn test_to_dot() {
+        let mut app = App::new();
+        let (_, info) = app.add_plugin_workload_with_info(Root);
+
+        let dot = info.to_dot();
+
+        assert!(dot.starts_with("digraph"), "{}", dot);
+        assert!(dot.contains("[label=\"Root\", shape=box]"), "{}", dot);
+        assert!(dot.contains("[label=\"adds\"]"), "{}", dot);
+        assert!(dot.contains("[label=\"A\", shape=cylinder]"), "{}", dot);
+        assert!(dot.contains("[label=\"update_pack\"]"), "{}", dot);
+        assert!(dot.contains("[label=\"provides\"]"), "{}", dot);
+        assert!(dot.trim_end().ends_with('}'), "{}", dot);
+    }
+
+    #[test]
+    fn test_to_mermaid() {
+        let mut app = App::new();
+        let (_, info) = app.add_plugin_workload_with_info(Root);
+
+        let mermaid = info.to_mermaid();
+
+        assert!(mermaid.starts_with("flowchart LR"), "{}", mermaid);
+        assert!(mermaid.contains("[\"Root\"]"), "{}", mermaid);
+        assert!(mermaid.contains("[(\"Settings\")]"), "{}", mermaid);
+        assert!(mermaid.contains("-->|\"adds\"|"), "{}", mermaid);
+    }
+}
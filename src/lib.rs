@@ -2,17 +2,37 @@ mod add_distinct;
 mod app;
 mod app_add_cycle;
 mod app_builder;
+mod app_handle;
+pub mod clock;
+mod commands;
+mod events;
+mod graph_export;
 mod plugin;
+mod plugin_toggles;
+mod run_condition;
+mod runner;
+pub mod stage;
+mod time;
 mod tracked_unique;
+#[cfg(any(test, feature = "tree"))]
+pub mod tree;
 mod type_names;
 mod update_one_to_one;
+mod update_pack;
 mod update_two_to_one;
 
 pub use add_distinct::*;
 pub use app::*;
 pub use app_builder::*;
+pub use app_handle::{AppHandle, AppHandleChannel, AppHandlePlugin, APP_HANDLE_LABEL};
+pub use commands::{CommandQueue, Commands};
+pub use events::{EventReader, EventWriter, Events};
 pub use plugin::*;
+pub use plugin_toggles::PluginToggles;
+pub use run_condition::*;
+pub use runner::{AppExit, AppRunner, FixedRate};
 pub use shipyard::*;
+pub use time::{FixedTimestep, Time, TimeClock, TimePlugin};
 pub use tracked_unique::*;
 pub use update_one_to_one::*;
 pub use update_two_to_one::*;
@@ -24,14 +44,14 @@ pub mod prelude {
         add_distinct::AddDistinct,
         app::App,
         app_builder::{AppBuilder, AppWorkload},
+        app_handle::{AppHandle, AppHandlePlugin},
+        commands::Commands,
+        events::{EventReader, EventWriter, Events},
         plugin::Plugin,
+        runner::{AppExit, AppRunner},
+        time::{FixedTimestep, Time, TimePlugin},
         update_one_to_one::UpdateOneToOne,
         update_two_to_one::UpdateTwoToOne,
     };
     pub use shipyard::*;
 }
-
-#[cfg(test)]
-mod test {
-    mod tree;
-}
@@ -14,4 +14,9 @@ pub trait Plugin: Any + Send + Sync {
     fn can_add_multiple_times(&self) -> bool {
         false
     }
+    /// If you override this to return true, then your plugin's systems (and the systems of plugins it adds) can be
+    /// switched off at runtime with [App::set_plugin_enabled](crate::App::set_plugin_enabled).
+    fn can_be_disabled(&self) -> bool {
+        false
+    }
 }
@@ -0,0 +1,66 @@
+//! Switch off every system contributed by a plugin at runtime, without rebuilding the workload.
+use std::{
+    any::{type_name, TypeId},
+    collections::{HashMap, HashSet},
+};
+
+use tracing::warn;
+
+use crate::prelude::*;
+
+/// Which plugins are currently disabled.
+///
+/// Only plugins returning `true` from [Plugin::can_be_disabled] can be switched off, since only their systems
+/// are placed behind a toggle when the workload is built. Use [App::set_plugin_enabled] or borrow this unique directly.
+#[derive(Component, Debug, Default)]
+pub struct PluginToggles {
+    /// Plugins which can be disabled
+    toggleable: HashMap<TypeId, &'static str>,
+    disabled: HashSet<TypeId>,
+}
+
+impl PluginToggles {
+    /// Enable or disable every system added by plugin `P` (including systems of plugins it added).
+    ///
+    /// Returns `false` if `P` can not be disabled.
+    pub fn set_enabled<P: Plugin>(&mut self, enabled: bool) -> bool {
+        let type_id = TypeId::of::<P>();
+        if !self.toggleable.contains_key(&type_id) {
+            warn!(
+                "Plugin({}) was not added with `Plugin::can_be_disabled`, so it cannot be toggled.",
+                type_name::<P>()
+            );
+            return false;
+        }
+
+        if enabled {
+            self.disabled.remove(&type_id);
+        } else {
+            self.disabled.insert(type_id);
+        }
+
+        true
+    }
+
+    pub fn is_enabled<P: Plugin>(&self) -> bool {
+        !self.disabled.contains(&TypeId::of::<P>())
+    }
+
+    /// Names of the plugins which are currently disabled
+    pub fn disabled(&self) -> Vec<&'static str> {
+        self.disabled
+            .iter()
+            .filter_map(|type_id| self.toggleable.get(type_id).copied())
+            .collect()
+    }
+
+    pub(crate) fn register(&mut self, type_id: TypeId, name: &'static str) {
+        self.toggleable.insert(type_id, name);
+    }
+
+    pub(crate) fn all_enabled(&self, plugins: &[(TypeId, &'static str)]) -> bool {
+        plugins
+            .iter()
+            .all(|(type_id, _)| !self.disabled.contains(type_id))
+    }
+}
@@ -0,0 +1,203 @@
+//! Conditions for [AppBuilder::add_system_if](crate::AppBuilder::add_system_if), which are systems returning `bool`.
+use std::{
+    any::{type_name, TypeId},
+    borrow::Cow,
+    collections::HashSet,
+    sync::{Arc, Mutex},
+};
+
+use tracing::{trace, warn};
+
+use crate::{prelude::*, PluginToggles};
+
+/// Rebuilds a system which was already converted once, so it can be added to another workload
+pub(crate) type SystemFactory = Arc<dyn Fn() -> WorkloadSystem + Send + Sync>;
+
+/// A condition checked before running the workload containing a conditional system
+#[derive(Clone)]
+pub(crate) struct RunCondition {
+    pub(crate) name: &'static str,
+    check: Arc<dyn Fn(&World) -> bool + Send + Sync>,
+}
+
+impl RunCondition {
+    pub(crate) fn new<B: 'static, C>(condition: C) -> Self
+    where
+        C: for<'s> System<'s, (), B, bool> + Clone + Send + Sync + 'static,
+    {
+        RunCondition {
+            name: type_name::<C>(),
+            check: Arc::new(move |world: &World| match world.run(condition.clone()) {
+                Ok(met) => met,
+                Err(error) => {
+                    warn!(
+                        condition = type_name::<C>(),
+                        %error,
+                        "run condition failed to borrow, skipping its system"
+                    );
+                    false
+                }
+            }),
+        }
+    }
+
+    /// Whether the condition is met, `false` if it can't borrow what it needs
+    pub(crate) fn check(&self, world: &World) -> bool {
+        (self.check)(world)
+    }
+}
+
+/// Everything gating one workload of an [AppWorkload]
+#[derive(Clone, Debug, Default)]
+pub(crate) struct WorkloadCondition {
+    /// Plugins which can disable this workload through [PluginToggles]
+    pub(crate) toggled_by: Vec<(TypeId, &'static str)>,
+    /// Systems of the workload gated by a [RunCondition], `None` if it has none
+    pub(crate) conditional: Option<Arc<ConditionalSystems>>,
+}
+
+impl WorkloadCondition {
+    pub(crate) fn is_empty(&self) -> bool {
+        self.toggled_by.is_empty() && self.conditional.is_none()
+    }
+
+    /// Whether the workload should run, tracing the reason it's skipped
+    pub(crate) fn should_run(&self, world: &World) -> bool {
+        if !self.toggled_by.is_empty() {
+            if let Ok(toggles) = world.borrow::<UniqueView<PluginToggles>>() {
+                if !toggles.all_enabled(&self.toggled_by) {
+                    trace!(plugins = ?toggles.disabled(), "skipped, plugin disabled");
+                    return false;
+                }
+            }
+        }
+
+        true
+    }
+
+    /// Name of the workload to run in place of `workload_name`, see [ConditionalSystems::workload_to_run]
+    pub(crate) fn workload_to_run(
+        &self,
+        world: &World,
+        workload_name: &Cow<'static, str>,
+    ) -> Cow<'static, str> {
+        match &self.conditional {
+            Some(conditional) => conditional.workload_to_run(world, workload_name),
+            None => workload_name.clone(),
+        }
+    }
+}
+
+/// Every system of a workload with at least one conditional system, in order.
+///
+/// The workload added to the world holds all of them, for when every condition is met. When some are not,
+/// a variant of the workload without the skipped systems runs instead, so the skipped systems borrow nothing
+/// while the others are still batched together.
+pub(crate) struct ConditionalSystems {
+    systems: Vec<(SystemFactory, Option<RunCondition>)>,
+    /// Which conditions were met for each variant already added to the world
+    variants: Mutex<HashSet<Vec<bool>>>,
+}
+
+impl ConditionalSystems {
+    pub(crate) fn new(systems: Vec<(SystemFactory, Option<RunCondition>)>) -> Self {
+        ConditionalSystems {
+            systems,
+            variants: Default::default(),
+        }
+    }
+
+    /// Check each system's condition, adding the variant of the workload without the skipped systems
+    /// to the world the first time these conditions are met.
+    pub(crate) fn workload_to_run(
+        &self,
+        world: &World,
+        workload_name: &Cow<'static, str>,
+    ) -> Cow<'static, str> {
+        let met = self
+            .systems
+            .iter()
+            .filter_map(|(_, condition)| condition.as_ref())
+            .map(|condition| {
+                let met = condition.check(world);
+                if !met {
+                    trace!(condition = condition.name, "skipped, run condition not met");
+                }
+                met
+            })
+            .collect::<Vec<_>>();
+        if met.iter().all(|met| *met) {
+            return workload_name.clone();
+        }
+
+        let variant_name: Cow<'static, str> = format!(
+            "{}?{}",
+            workload_name,
+            met.iter()
+                .map(|met| if *met { '1' } else { '0' })
+                .collect::<String>()
+        )
+        .into();
+        let mut variants = self.variants.lock().unwrap();
+        if !variants.contains(&met) {
+            let mut conditions_met = met.iter();
+            let mut variant = WorkloadBuilder::new(variant_name.clone());
+            for (rebuild, condition) in &self.systems {
+                if condition.is_none() || *conditions_met.next().unwrap() {
+                    variant = variant.with_system(rebuild());
+                }
+            }
+            variant
+                .add_to_world(world)
+                .expect("variant of a workload which was already added");
+            variants.insert(met);
+        }
+
+        variant_name
+    }
+}
+
+impl std::fmt::Debug for ConditionalSystems {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list()
+            .entries(self.systems.iter().map(|(_, condition)| condition))
+            .finish()
+    }
+}
+
+impl std::fmt::Debug for RunCondition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("RunCondition").field(&self.name).finish()
+    }
+}
+
+/// Run condition: the [TrackedUnique](crate::TrackedUniquePlugin) `T` was inserted or modified this update
+pub fn tracked_unique_modified<T: Component<Tracking = track::All>>(
+    uv_tracked_unique_t: UniqueView<T>,
+) -> bool {
+    uv_tracked_unique_t.is_inserted() || uv_tracked_unique_t.is_modified()
+}
+
+/// Run condition: the storage of `T` has inserted or modified components
+///
+/// Pair with [AppBuilder::update_pack](crate::AppBuilder::update_pack) so the tracking is reset every update.
+pub fn storage_inserted_or_modified<T: Component<Tracking = track::All>>(v_t: View<T>) -> bool {
+    v_t.inserted().iter().next().is_some() || v_t.modified().iter().next().is_some()
+}
+
+/// Run condition: the unique flag `F` is set
+///
+/// ```
+/// # use shipyard_app::prelude::*;
+/// #[derive(Clone, Component)]
+/// struct Paused(bool);
+///
+/// impl From<Paused> for bool {
+///     fn from(paused: Paused) -> bool {
+///         paused.0
+///     }
+/// }
+/// ```
+pub fn unique_flag_set<F: Component + Clone + Into<bool>>(uv_flag: UniqueView<F>) -> bool {
+    uv_flag.clone().into()
+}
@@ -0,0 +1,225 @@
+//! Main loops for an [App], see [App::run_loop].
+use std::time::Duration;
+
+use shipyard::*;
+use tracing::{trace, trace_span};
+
+use crate::{clock::Clock, clock::SystemClock, App};
+
+/// Set by systems (or from outside) to stop [App::run_loop] after the current update.
+///
+/// Added to the world by [App::set_runner] (or [AppBuilder::set_runner](crate::AppBuilder::set_runner)) and [App::run_loop].
+#[derive(Component, Debug, Default)]
+pub struct AppExit(bool);
+
+impl AppExit {
+    pub fn exit(&mut self) {
+        self.0 = true;
+    }
+
+    pub fn is_requested(&self) -> bool {
+        self.0
+    }
+}
+
+/// How [App::run_loop] drives [App::update].
+///
+/// Every runner stops early once [AppExit] is requested.
+pub enum AppRunner {
+    /// Update once
+    RunOnce,
+    /// Update `n` times
+    RunN(usize),
+    /// Update until the condition returns `true`, checked after each update
+    RunUntil(Box<dyn Fn(&World) -> bool + Send + Sync>),
+    /// Update at a fixed rate, sleeping on the clock between updates, until [AppExit] is requested.
+    /// Created with [AppRunner::fixed_rate] or [AppRunner::fixed_rate_with_clock].
+    FixedRate(FixedRate),
+}
+
+/// Period and clock of an [AppRunner::FixedRate] runner
+pub struct FixedRate {
+    period: Duration,
+    clock: Box<dyn Clock>,
+}
+
+impl FixedRate {
+    /// Time between the start of consecutive updates
+    pub fn period(&self) -> Duration {
+        self.period
+    }
+}
+
+impl Default for AppRunner {
+    fn default() -> Self {
+        AppRunner::RunOnce
+    }
+}
+
+impl std::fmt::Debug for AppRunner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AppRunner::RunOnce => f.write_str("RunOnce"),
+            AppRunner::RunN(n) => f.debug_tuple("RunN").field(n).finish(),
+            AppRunner::RunUntil(_) => f.write_str("RunUntil(..)"),
+            AppRunner::FixedRate(fixed_rate) => f
+                .debug_struct("FixedRate")
+                .field("period", &fixed_rate.period)
+                .finish(),
+        }
+    }
+}
+
+impl AppRunner {
+    pub fn run_until<F: Fn(&World) -> bool + Send + Sync + 'static>(exit_condition: F) -> Self {
+        AppRunner::RunUntil(Box::new(exit_condition))
+    }
+
+    /// Update `hz` times per second on the [SystemClock]
+    ///
+    /// # Panics
+    /// Panics if `hz` is not a finite number above zero.
+    #[track_caller]
+    pub fn fixed_rate(hz: f64) -> Self {
+        AppRunner::fixed_rate_with_clock(hz, SystemClock::default())
+    }
+
+    /// Update `hz` times per second on `clock`
+    ///
+    /// # Panics
+    /// Panics if `hz` is not a finite number above zero.
+    #[track_caller]
+    pub fn fixed_rate_with_clock<C: Clock + 'static>(hz: f64, clock: C) -> Self {
+        assert!(
+            hz.is_finite() && hz > 0.0,
+            "Fixed rate must be a finite number of updates per second above zero, got {}",
+            hz
+        );
+        AppRunner::FixedRate(FixedRate {
+            period: Duration::from_secs_f64(1.0 / hz),
+            clock: Box::new(clock),
+        })
+    }
+
+    /// Returns the number of updates run
+    pub(crate) fn run(&self, app: &App) -> usize {
+        let span = trace_span!("AppRunner::run", runner = ?self);
+        let _span = span.enter();
+        let mut updates = 0;
+        match self {
+            AppRunner::RunOnce => {
+                app.update();
+                updates += 1;
+            }
+            AppRunner::RunN(n) => {
+                while updates < *n && !exit_requested(app) {
+                    app.update();
+                    updates += 1;
+                }
+            }
+            AppRunner::RunUntil(exit_condition) => loop {
+                app.update();
+                updates += 1;
+                if exit_condition(&app.world) || exit_requested(app) {
+                    break;
+                }
+            },
+            AppRunner::FixedRate(FixedRate { period, clock }) => {
+                let period = *period;
+                while !exit_requested(app) {
+                    let started = clock.now();
+                    app.update();
+                    updates += 1;
+                    let elapsed = clock.now() - started;
+                    match period.checked_sub(elapsed) {
+                        Some(remaining) => clock.sleep(remaining),
+                        None => trace!(?elapsed, ?period, "update exceeded fixed rate period"),
+                    }
+                }
+            }
+        }
+
+        updates
+    }
+}
+
+fn exit_requested(app: &App) -> bool {
+    app.world
+        .borrow::<UniqueView<AppExit>>()
+        .map(|exit| exit.is_requested())
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod runner_tests {
+    use std::time::Duration;
+
+    use crate::{
+        clock::{Clock, ManualClock},
+        prelude::*,
+    };
+
+    #[derive(Component, Default)]
+    struct Updates(usize);
+    struct CountingPlugin;
+
+    fn count_and_exit_at_five(
+        mut updates: UniqueViewMut<Updates>,
+        mut exit: UniqueViewMut<AppExit>,
+    ) {
+        updates.0 += 1;
+        if updates.0 == 5 {
+            exit.exit();
+        }
+    }
+
+    impl Plugin for CountingPlugin {
+        fn build(&self, app: &mut AppBuilder) {
+            app.add_unique(Updates::default())
+                .add_system(count_and_exit_at_five)
+                .set_runner(AppRunner::RunN(3));
+        }
+    }
+
+    fn updates(app: &App) -> usize {
+        app.world.borrow::<UniqueView<Updates>>().unwrap().0
+    }
+
+    #[test]
+    fn test_run_n_from_builder() {
+        let mut app = App::new();
+        app.add_plugin_workload(CountingPlugin);
+
+        assert_eq!(app.run_loop(), 3);
+        assert_eq!(updates(&app), 3);
+    }
+
+    #[test]
+    fn test_run_until() {
+        let mut app = App::new();
+        app.add_plugin_workload(CountingPlugin);
+        app.set_runner(AppRunner::run_until(|world| {
+            world.borrow::<UniqueView<Updates>>().unwrap().0 == 2
+        }));
+
+        assert_eq!(app.run_loop(), 2);
+    }
+
+    #[test]
+    fn test_fixed_rate_stops_on_app_exit() {
+        let mut app = App::new();
+        app.add_plugin_workload(CountingPlugin);
+        let clock = ManualClock::default();
+        app.set_runner(AppRunner::fixed_rate_with_clock(10.0, clock.clone()));
+
+        assert_eq!(app.run_loop(), 5);
+        // slept a full period after each update, as updates take no time on the manual clock
+        assert_eq!(clock.now(), Duration::from_millis(500));
+    }
+
+    #[test]
+    #[should_panic(expected = "above zero")]
+    fn test_fixed_rate_rejects_zero_hz() {
+        AppRunner::fixed_rate_with_clock(0.0, ManualClock::default());
+    }
+}
@@ -0,0 +1,25 @@
+//! Names of the built-in stages of every plugin workload, in the order they run.
+//!
+//! Each stage with systems becomes its own shipyard workload, so every system of a stage finishes
+//! before any system of the next stage starts. Plugins can add their own stages relative to these
+//! with [AppBuilder::add_stage_before](crate::AppBuilder::add_stage_before) and
+//! [AppBuilder::add_stage_after](crate::AppBuilder::add_stage_after).
+
+/// Added before [FIRST] by the [TimePlugin](crate::TimePlugin), so [Time](crate::Time) is updated before any other system runs
+pub const TIME: &str = "time";
+/// Runs before all other built-in stages
+pub const FIRST: &str = "first";
+/// Runs before [FIXED_UPDATE]
+pub const PRE_UPDATE: &str = "pre_update";
+/// Runs zero or more times per update, once per [FixedTimestep](crate::FixedTimestep) step accumulated from [Time](crate::Time).
+/// Systems are added with [AppBuilder::add_fixed_system](crate::AppBuilder::add_fixed_system)
+pub const FIXED_UPDATE: &str = "fixed_update";
+/// Default stage for [AppBuilder::add_system](crate::AppBuilder::add_system)
+pub const UPDATE: &str = "update";
+/// Runs after [UPDATE]
+pub const POST_UPDATE: &str = "post_update";
+/// Runs after all other built-in stages, reset systems run at the end of the final stage
+pub const LAST: &str = "last";
+
+pub(crate) const DEFAULT_STAGES: [&str; 6] =
+    [FIRST, PRE_UPDATE, FIXED_UPDATE, UPDATE, POST_UPDATE, LAST];
@@ -0,0 +1,289 @@
+//! Time keeping uniques, and the accumulator behind the [stage::FIXED_UPDATE](crate::stage::FIXED_UPDATE) stage.
+use std::{sync::Arc, time::Duration};
+
+use shipyard::*;
+use tracing::{trace, trace_span, warn};
+
+use crate::{
+    clock::{Clock, SystemClock},
+    stage, AppBuilder, Plugin,
+};
+
+/// Time passed between updates.
+///
+/// Updated at the start of every update by the [TimePlugin], or advanced manually with [Time::advance].
+/// Tracked, so [Tracked](crate::Tracked) views can tell whether it changed during the update.
+#[derive(Clone, Component, Debug, Default)]
+#[track(All)]
+pub struct Time {
+    delta: Duration,
+    elapsed: Duration,
+    frame: u64,
+    /// Instant on the [Clock]'s timeline when the first update started
+    startup: Option<Duration>,
+    last_update: Option<Duration>,
+}
+
+impl Time {
+    /// Number of times time was advanced, the first update is frame 1
+    pub fn frame(&self) -> u64 {
+        self.frame
+    }
+
+    /// Instant on the [Clock]'s timeline when the first update started
+    pub fn startup(&self) -> Option<Duration> {
+        self.startup
+    }
+
+    /// Time passed during the last update
+    pub fn delta(&self) -> Duration {
+        self.delta
+    }
+
+    /// Total time passed across every update
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    pub fn advance(&mut self, delta: Duration) {
+        self.delta = delta;
+        self.elapsed += delta;
+        self.frame += 1;
+    }
+
+    /// Advance to the clock reading `now`, the first update has no delta
+    fn advance_to(&mut self, now: Duration) {
+        let last_update = *self.last_update.get_or_insert(now);
+        self.startup.get_or_insert(now);
+        self.last_update = Some(now);
+        self.advance(now.checked_sub(last_update).unwrap_or_default());
+    }
+}
+
+/// The [Clock] [TimePlugin] reads
+#[derive(Component)]
+pub struct TimeClock(Arc<dyn Clock>);
+
+/// Adds the [Time] tracked unique and updates it in the [stage::TIME] stage of every update.
+///
+/// Only the first TimePlugin added to an [App](crate::App) updates [Time], so it advances once per cycle.
+/// Later ones (e.g. in a workload of fixed systems run in the same cycle) only depend on [Time] and their clock is dropped.
+/// To drive [Time] by hand, use a [ManualClock](crate::clock::ManualClock) or skip the plugin and call [Time::advance].
+///
+/// ```
+/// use shipyard_app::{clock::ManualClock, prelude::*};
+/// use std::time::Duration;
+///
+/// let clock = ManualClock::default();
+/// let mut app = App::new();
+/// app.add_plugin_workload(TimePlugin::with_clock(clock.clone()));
+///
+/// app.update();
+/// clock.advance(Duration::from_millis(16));
+/// app.update();
+///
+/// let time = app.world.borrow::<UniqueView<Time>>().unwrap();
+/// assert_eq!(time.delta(), Duration::from_millis(16));
+/// assert_eq!(time.frame(), 2);
+/// ```
+#[derive(Clone)]
+pub struct TimePlugin {
+    clock: Arc<dyn Clock>,
+}
+
+impl Default for TimePlugin {
+    fn default() -> Self {
+        TimePlugin::with_clock(SystemClock::default())
+    }
+}
+
+impl TimePlugin {
+    pub fn with_clock<C: Clock + 'static>(clock: C) -> Self {
+        TimePlugin {
+            clock: Arc::new(clock),
+        }
+    }
+}
+
+impl Plugin for TimePlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        if !app.app.claim_time_updates() {
+            warn!("TimePlugin's clock dropped, Time is already updated by the TimePlugin of another workload");
+            app.depends_on_unique::<Time>("Time updated by the TimePlugin of another workload");
+            return;
+        }
+
+        app.add_tracked_value(Time::default())
+            .add_unique(TimeClock(self.clock.clone()))
+            .tracks::<Time>("TimePlugin updates Time every update")
+            .add_stage_before(stage::FIRST, stage::TIME)
+            .add_system_to_stage(stage::TIME, update_time);
+    }
+}
+
+fn update_time(uv_clock: UniqueView<TimeClock>, mut uvm_time: UniqueViewMut<Time>) {
+    let span = trace_span!("update_time");
+    let _span = span.enter();
+    uvm_time.advance_to(uv_clock.0.now());
+}
+
+/// Configures how often systems added with [AppBuilder::add_fixed_system](crate::AppBuilder::add_fixed_system) run.
+///
+/// Each update, [Time::delta] is added to an accumulator and the fixed stage runs once for every whole `step` accumulated,
+/// up to `max_steps_per_update` times. Anything beyond the cap is dropped so a slow update can't snowball.
+#[derive(Clone, Component, Debug)]
+pub struct FixedTimestep {
+    step: Duration,
+    max_steps_per_update: u32,
+    accumulator: Duration,
+    steps_this_update: u32,
+}
+
+impl Default for FixedTimestep {
+    /// 60 steps per second, catching up at most 5 steps per update
+    fn default() -> Self {
+        FixedTimestep::new(Duration::from_secs(1) / 60)
+    }
+}
+
+impl FixedTimestep {
+    pub fn new(step: Duration) -> Self {
+        assert!(
+            step > Duration::from_secs(0),
+            "Fixed timestep must be positive"
+        );
+        FixedTimestep {
+            step,
+            max_steps_per_update: 5,
+            accumulator: Duration::from_secs(0),
+            steps_this_update: 0,
+        }
+    }
+
+    pub fn with_max_steps_per_update(mut self, max_steps_per_update: u32) -> Self {
+        self.max_steps_per_update = max_steps_per_update;
+        self
+    }
+
+    /// Simulated time of each fixed step
+    pub fn step(&self) -> Duration {
+        self.step
+    }
+
+    /// Time accumulated towards the next step
+    pub fn accumulator(&self) -> Duration {
+        self.accumulator
+    }
+
+    /// How far along the next step is (between 0 and 1), for interpolating between fixed states
+    pub fn overstep_fraction(&self) -> f64 {
+        self.accumulator.as_secs_f64() / self.step.as_secs_f64()
+    }
+
+    pub fn steps_this_update(&self) -> u32 {
+        self.steps_this_update
+    }
+
+    /// Accumulate `delta` and take as many whole steps as allowed
+    pub(crate) fn expend(&mut self, delta: Duration) -> u32 {
+        self.accumulator += delta;
+        let mut steps = 0;
+        while self.accumulator >= self.step && steps < self.max_steps_per_update {
+            self.accumulator -= self.step;
+            steps += 1;
+        }
+
+        if self.accumulator >= self.step {
+            warn!(
+                dropped = ?self.accumulator - self.step,
+                max_steps = self.max_steps_per_update,
+                "Fixed timestep fell behind, dropping accumulated time"
+            );
+            // drop the whole steps, keeping how far along the next step is
+            self.accumulator =
+                Duration::from_nanos((self.accumulator.as_nanos() % self.step.as_nanos()) as u64);
+        }
+
+        self.steps_this_update = steps;
+        steps
+    }
+}
+
+/// Number of times to run the fixed stage during this update
+pub(crate) fn expend_fixed_timestep(world: &World) -> u32 {
+    let delta = match world.borrow::<UniqueView<Time>>() {
+        Ok(time) => time.delta(),
+        Err(_) => return 0,
+    };
+
+    match world.borrow::<UniqueViewMut<FixedTimestep>>() {
+        Ok(mut fixed_timestep) => {
+            let steps = fixed_timestep.expend(delta);
+            trace!(steps, ?delta, "fixed timestep");
+            steps
+        }
+        Err(_) => 0,
+    }
+}
+
+#[cfg(test)]
+mod time_tests {
+    use super::*;
+    use crate::{clock::ManualClock, App};
+
+    struct MainPlugin(ManualClock);
+    struct FixedPlugin(ManualClock);
+
+    impl Plugin for MainPlugin {
+        fn build(&self, app: &mut AppBuilder) {
+            app.add_plugin(TimePlugin::with_clock(self.0.clone()));
+        }
+    }
+
+    impl Plugin for FixedPlugin {
+        fn build(&self, app: &mut AppBuilder) {
+            app.add_plugin(TimePlugin::with_clock(self.0.clone()));
+        }
+    }
+
+    #[test]
+    fn test_expend_takes_whole_steps_and_keeps_the_remainder() {
+        let mut fixed = FixedTimestep::new(Duration::from_millis(10));
+        assert_eq!(fixed.expend(Duration::from_millis(25)), 2);
+        assert_eq!(fixed.accumulator(), Duration::from_millis(5));
+        assert_eq!(fixed.expend(Duration::from_millis(5)), 1);
+        assert_eq!(fixed.accumulator(), Duration::from_millis(0));
+        assert_eq!(fixed.expend(Duration::from_millis(3)), 0);
+    }
+
+    #[test]
+    fn test_expend_caps_catch_up_steps() {
+        let mut fixed = FixedTimestep::new(Duration::from_millis(10)).with_max_steps_per_update(3);
+        assert_eq!(fixed.expend(Duration::from_millis(1005)), 3);
+        assert_eq!(fixed.accumulator(), Duration::from_millis(5));
+        assert_eq!(fixed.steps_this_update(), 3);
+
+        // the dropped steps don't carry over to the next update
+        assert_eq!(fixed.expend(Duration::from_millis(10)), 1);
+        assert_eq!(fixed.accumulator(), Duration::from_millis(5));
+    }
+
+    #[test]
+    fn test_time_plugin_in_two_workloads_updates_time_once() {
+        let clock = ManualClock::default();
+        let mut app = App::new();
+        let main = app.add_plugin_workload_with_info(MainPlugin(clock.clone()));
+        let fixed = app.add_plugin_workload_with_info(FixedPlugin(clock.clone()));
+        let (cycle, _) = app
+            .add_cycle(vec![main, fixed])
+            .expect("Time tracked by a single workload");
+
+        cycle.run(&app);
+        clock.advance(Duration::from_millis(16));
+        cycle.run(&app);
+
+        let time = app.world.borrow::<UniqueView<Time>>().unwrap();
+        assert_eq!(time.frame(), 2);
+        assert_eq!(time.delta(), Duration::from_millis(16));
+    }
+}
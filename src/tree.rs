@@ -0,0 +1,1435 @@
+//! # Tree
+//!
+//! Tree provides a very simple way to manipulate the ordering of elements in a CRDT fashion enabling
+//! a minimal amount of effort to reorder entities and resolve multiple concurrent reorderings.
+//!
+//! This is intended to be used alongside an additional decorating system which can index the child positions
+//! in the tree whenever a movement occurs and to keep everything in check.
+//!
+//! So, you would have a tree_reordering system (for managing reordering commands operating on the tree components) and
+//! a tree_indexing system for updating the tree_index components.
+//!
+//! The Advantages to this approach are to
+//!  - simplify the source of truth for more reliable ser & de
+//!  - Reduce the size of the seriallized form
+//!  - Less blocking systems (if something only cares that the ChildOf / Ordering has changed and the system does not
+//!    look at the indexed outputs, then it can run concurrently with the tree_indexing system)
+//!
+//! Siblings are ordered by [Ordered] keys by default. Trees synced between peers can use [FractionalIndex] keys instead,
+//! see [TreePlugin::with_order]. Moves from every replica are applied in the same order by the [MoveLog].
+//!
+//! Deleting an entity leaves its children to the [OrphanPolicy], see [TreePlugin::with_orphan_policy].
+use std::marker::PhantomData;
+
+use crate::*;
+
+mod fractional_index;
+mod indexing;
+mod move_log;
+mod node;
+mod orphans;
+mod reordering;
+mod violation;
+
+pub use fractional_index::FractionalIndex;
+pub use indexing::{tree_indexing, ParentIndex, SiblingIndex};
+pub use move_log::{LamportTimestamp, LoggedMove, MoveLog, ReplicaId};
+pub use node::*;
+pub use orphans::OrphanPolicy;
+pub use reordering::{tree_reordering, MoveCmd, MoveToPlace};
+pub use violation::{RejectedChildOf, TreeViolation};
+
+/// Label of the [tree_reordering] system added by [TreePlugin]
+pub const TREE_REORDERING_LABEL: &str = "tree_reordering";
+/// Label of the [tree_indexing] system added by [TreePlugin]
+pub const TREE_INDEXING_LABEL: &str = "tree_indexing";
+
+/// Queue of [MoveCmd]s applied by the [tree_reordering] system
+#[derive(Component, Debug)]
+pub struct MoveCommands<O: SiblingOrder = Ordered>(Vec<MoveCmd<O>>);
+
+impl<O: SiblingOrder> Default for MoveCommands<O> {
+    fn default() -> Self {
+        MoveCommands(Vec::new())
+    }
+}
+
+impl<O: SiblingOrder> MoveCommands<O> {
+    pub fn push(&mut self, cmd: MoveCmd<O>) {
+        self.0.push(cmd);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+/// Registers the [MoveCommands], [MoveLog] and [OrphanPolicy] uniques, the [TreeViolation] event, and the
+/// [tree_reordering] and [tree_indexing] systems for [ChildOf]s ordered by `O`
+pub struct TreePlugin<O: SiblingOrder = Ordered> {
+    replica: ReplicaId,
+    orphan_policy: OrphanPolicy,
+    order: PhantomData<O>,
+}
+
+impl Default for TreePlugin {
+    /// Tree of [ChildOf]s ordered by [Ordered] keys which isn't synced, its moves are stamped with `ReplicaId(0)`
+    fn default() -> Self {
+        TreePlugin::with_order(ReplicaId::default())
+    }
+}
+
+impl<O: SiblingOrder> TreePlugin<O> {
+    /// Tree of [ChildOf]s ordered by `O` keys, e.g. `TreePlugin::<FractionalIndex>::with_order(ReplicaId(1))`.
+    ///
+    /// `replica` is stamped on local moves, and must be unique among the peers syncing this tree.
+    pub fn with_order(replica: ReplicaId) -> Self {
+        TreePlugin {
+            replica,
+            orphan_policy: OrphanPolicy::default(),
+            order: PhantomData,
+        }
+    }
+
+    /// What happens to the children of deleted entities, [OrphanPolicy::DetachToRoot] by default
+    pub fn with_orphan_policy(mut self, orphan_policy: OrphanPolicy) -> Self {
+        self.orphan_policy = orphan_policy;
+        self
+    }
+}
+
+impl<O: SiblingOrder> Plugin for TreePlugin<O> {
+    fn build(&self, app: &mut AppBuilder) {
+        // TreePlugin clears updates on its own.
+        app.add_unique(MoveCommands::<O>::default())
+            .add_unique(MoveLog::<O>::new(self.replica))
+            .add_unique(self.orphan_policy)
+            .add_event::<TreeViolation<O>>()
+            .update_pack_without_reset::<ChildOf<O>>("update in response to ChildOf changes")
+            .add_system_labeled(TREE_REORDERING_LABEL, tree_reordering::<O>)
+            .before(TREE_INDEXING_LABEL)
+            .add_system_labeled(TREE_INDEXING_LABEL, tree_indexing::<O>);
+    }
+}
+
+/// A spattering of tests to check things
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup_world_with_index_system() -> World {
+        // Create a new world
+        let world = World::new();
+        world
+            .add_unique(Events::<TreeViolation>::default())
+            .unwrap();
+        // Commands are only applied when running through an App, the default OrphanPolicy doesn't record any
+        world.add_unique(OrphanPolicy::default()).unwrap();
+        world.add_unique(CommandQueue::default()).unwrap();
+
+        // Add the indexing workload
+        let indexing = WorkloadBuilder::new("indexing");
+
+        indexing
+            .with_system(indexing::tree_indexing::<Ordered>)
+            .with_system(|mut vm_child_of: ViewMut<ChildOf>| {
+                vm_child_of.clear_all_inserted_and_modified();
+                vm_child_of.take_deleted();
+            })
+            .add_to_world(&world)
+            .unwrap();
+
+        world
+    }
+
+    #[test]
+    fn insert_single_entity_with_no_children() {
+        let world = setup_world_with_index_system();
+        // Add a parent and child entity
+        let a = world
+            .run(|mut entities: EntitiesViewMut| entities.add_entity((), ()))
+            .unwrap();
+
+        // Run the indexing workload
+        world.run_default().unwrap();
+
+        // There should be no indexes at all
+        world
+            .run(
+                |v_parent_index: View<ParentIndex>, v_sibling_index: View<SiblingIndex>| {
+                    assert_eq!(v_parent_index.contains(a), false);
+                    assert_eq!(v_sibling_index.contains(a), false);
+                },
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn insert_single_child() {
+        let world = setup_world_with_index_system();
+        // Add a parent and child entity
+        let (a, a1) = world
+            .run(
+                |mut entities: EntitiesViewMut, mut vm_child_of: ViewMut<ChildOf>| {
+                    let a = entities.add_entity((), ());
+                    let a1 = entities.add_entity(&mut vm_child_of, ChildOf(a, Ordered::hinted(1)));
+                    (a, a1)
+                },
+            )
+            .unwrap();
+
+        // Run the indexing workload
+        world.run_default().unwrap();
+
+        // Run an anonymous system to verify the state of the indexes
+        world
+            .run(
+                |v_parent_index: View<ParentIndex>, v_sibling_index: View<SiblingIndex>| {
+                    // The root node should not have a sibling index
+                    v_sibling_index
+                        .get(a)
+                        .expect_err("should not have sibling data");
+
+                    // The root node should have a parent index
+                    let c: &ParentIndex = v_parent_index.get(a).expect("has children");
+
+                    // The root node should have exactly one child
+                    assert_eq!(parent_children_ids(c), vec![a1], "should be in order");
+
+                    // The child should not have a parent index
+                    assert_eq!(v_parent_index.contains(a1), false);
+
+                    // The child should have a sibling index, with no siblings and the parent being the root node
+                    let a1_sib: &SiblingIndex =
+                        v_sibling_index.get(a1).expect("should have sibling data");
+                    assert_eq!(a1_sib.prev_sibling, None, "only child");
+                    assert_eq!(a1_sib.next_sibling, None, "only child");
+                    assert_eq!(a1_sib.parent_node, a)
+                },
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn insert_many_siblings() {
+        let world = setup_world_with_index_system();
+        // Add a parent entity with many children
+        let (a, a1, a2, a3) = world
+            .run(
+                |mut entities: EntitiesViewMut, mut vm_child_of: ViewMut<ChildOf>| {
+                    let a = entities.add_entity((), ());
+                    let a1 = entities.add_entity(&mut vm_child_of, ChildOf(a, Ordered::hinted(1)));
+                    let a2 = entities.add_entity(&mut vm_child_of, ChildOf(a, Ordered::hinted(2)));
+                    let a3 = entities.add_entity(&mut vm_child_of, ChildOf(a, Ordered::hinted(3)));
+
+                    (a, a1, a2, a3)
+                },
+            )
+            .unwrap();
+
+        // Run the indexing workload
+        world.run_default().unwrap();
+
+        // Run an anonymous system to verify the state of the indexes
+        world
+            .run(
+                |v_parent_index: View<ParentIndex>, v_sibling_index: View<SiblingIndex>| {
+                    // The root node should not have a sibling index
+                    v_sibling_index
+                        .get(a)
+                        .expect_err("should not have sibling data");
+
+                    // The root node should have a parent index
+                    let c: &ParentIndex = v_parent_index.get(a).expect("has children");
+
+                    // The root node should have many children
+                    assert_eq!(
+                        parent_children_ids(c),
+                        vec![a1, a2, a3],
+                        "should be in order"
+                    );
+
+                    // The children should not have a parent indexes
+                    assert_eq!(v_parent_index.contains(a1), false);
+                    assert_eq!(v_parent_index.contains(a2), false);
+                    assert_eq!(v_parent_index.contains(a3), false);
+
+                    // The first sibling should not have a prev sibling but have a next sibling
+                    let a1_sib = v_sibling_index.get(a1).expect("should have sibling data");
+                    assert_eq!(a1_sib.prev_sibling, None, "only child");
+                    assert_eq!(a1_sib.next_sibling.unwrap().1, a2, "has sibling");
+
+                    // The middle sibling should have both prev and next siblings
+                    let a2_sib = v_sibling_index.get(a2).expect("should have sibling data");
+                    assert_eq!(a2_sib.prev_sibling.unwrap().1, a1, "has sibling");
+                    assert_eq!(a2_sib.next_sibling.unwrap().1, a3, "has sibling");
+
+                    // The last sibling should have a prev sibling but not have a next sibling
+                    let a3_sib = v_sibling_index.get(a3).expect("should have sibling data");
+                    assert_eq!(a3_sib.prev_sibling.unwrap().1, a2, "has sibling");
+                    assert_eq!(a3_sib.next_sibling, None, "only child");
+                },
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn insert_deep_children() {
+        let world = setup_world_with_index_system();
+        let (a, a1, a2, a3) = world
+            .run(
+                |mut entities: EntitiesViewMut, mut vm_child_of: ViewMut<ChildOf>| {
+                    let a = entities.add_entity((), ());
+                    let a1 = entities.add_entity(&mut vm_child_of, ChildOf(a, Ordered::hinted(1)));
+                    let a2 = entities.add_entity(&mut vm_child_of, ChildOf(a1, Ordered::hinted(2)));
+                    let a3 = entities.add_entity(&mut vm_child_of, ChildOf(a2, Ordered::hinted(3)));
+
+                    (a, a1, a2, a3)
+                },
+            )
+            .unwrap();
+
+        // Run the indexing workload
+        world.run_default().unwrap();
+
+        // Run an anonymous system to verify the state of the indexes
+        world
+            .run(
+                |v_parent_index: View<ParentIndex>, v_sibling_index: View<SiblingIndex>| {
+                    // Each entity should have a parent index containing one child
+                    let a_pi: &ParentIndex = v_parent_index.get(a).expect("has children");
+                    assert_eq!(parent_children_ids(a_pi), vec![a1]);
+
+                    let a1_pi: &ParentIndex = v_parent_index.get(a1).expect("has children");
+                    assert_eq!(parent_children_ids(a1_pi), vec![a2]);
+
+                    let a2_pi: &ParentIndex = v_parent_index.get(a2).expect("has children");
+                    assert_eq!(parent_children_ids(a2_pi), vec![a3]);
+
+                    // The leaf entity should not have a parent index
+                    assert_eq!(v_parent_index.contains(a3), false);
+
+                    // Each non-root entity should have a sibling index that is empty
+                    let a1_sib = v_sibling_index.get(a1).expect("should have sibling data");
+                    assert_eq!(a1_sib.prev_sibling, None, "only child");
+                    assert_eq!(a1_sib.next_sibling, None, "only child");
+
+                    let a2_sib = v_sibling_index.get(a2).expect("should have sibling data");
+                    assert_eq!(a2_sib.prev_sibling, None, "only child");
+                    assert_eq!(a2_sib.next_sibling, None, "only child");
+
+                    let a3_sib = v_sibling_index.get(a3).expect("should have sibling data");
+                    assert_eq!(a3_sib.prev_sibling, None, "only child");
+                    assert_eq!(a3_sib.next_sibling, None, "only child");
+                },
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn update_entity_as_child_of_self() {
+        let world = setup_world_with_index_system();
+        let a = world
+            .run(
+                |mut entities: EntitiesViewMut, mut vm_child_of: ViewMut<ChildOf>| {
+                    let a = entities.add_entity((), ());
+                    entities.add_component(a, &mut vm_child_of, ChildOf(a, Ordered::hinted(1)));
+
+                    a
+                },
+            )
+            .unwrap();
+
+        // Run the indexing workload
+        world.run_default().unwrap();
+
+        world
+            .run(
+                |v_child_of: View<ChildOf>,
+                 v_rejected_child_of: View<RejectedChildOf>,
+                 v_parent_index: View<ParentIndex>,
+                 v_sibling_index: View<SiblingIndex>| {
+                    // The ChildOf is moved aside instead of being indexed
+                    assert_eq!(v_child_of.contains(a), false);
+                    assert_eq!(
+                        v_rejected_child_of
+                            .get(a)
+                            .ok()
+                            .map(|rejected| rejected.0.clone()),
+                        Some(ChildOf(a, Ordered::hinted(1)))
+                    );
+                    assert_eq!(v_parent_index.contains(a), false);
+                    assert_eq!(v_sibling_index.contains(a), false);
+                },
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn delete_only_child() {
+        let world = setup_world_with_index_system();
+        // Add a parent and child entity
+        let (a, a1) = world
+            .run(
+                |mut entities: EntitiesViewMut, mut vm_child_of: ViewMut<ChildOf>| {
+                    let a = entities.add_entity((), ());
+                    let a1 = entities.add_entity(&mut vm_child_of, ChildOf(a, Ordered::hinted(1)));
+
+                    (a, a1)
+                },
+            )
+            .unwrap();
+
+        // Run the indexing workload
+        world.run_default().unwrap();
+
+        // Delete the child entity
+        world
+            .run(|mut vm_child_of: ViewMut<ChildOf>| {
+                // remove should not be used as it is untracked for whatever reason...
+                vm_child_of.delete(a1);
+            })
+            .unwrap();
+
+        // Run the indexing workload
+        world.run_default().unwrap();
+
+        world
+            .run(
+                |v_parent_index: View<ParentIndex>, v_sibling_index: View<SiblingIndex>| {
+                    // There should be no sibling index for the previous parent
+                    assert_eq!(v_sibling_index.contains(a), false);
+
+                    // TODO: Should there be a parent index any more with no children?
+                    // There should be a parent index with no children
+                    let c: &ParentIndex = v_parent_index.get(a).expect("has a parent index");
+                    assert_eq!(parent_children_ids(c), vec![]);
+                },
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn delete_first_and_last_siblings() {
+        let world = setup_world_with_index_system();
+        let (a, a1, a2, a3) = world
+            .run(
+                |mut entities: EntitiesViewMut, mut vm_child_of: ViewMut<ChildOf>| {
+                    let a = entities.add_entity((), ());
+                    let a1 = entities.add_entity(&mut vm_child_of, ChildOf(a, Ordered::hinted(1)));
+                    let a2 = entities.add_entity(&mut vm_child_of, ChildOf(a, Ordered::hinted(2)));
+                    let a3 = entities.add_entity(&mut vm_child_of, ChildOf(a, Ordered::hinted(3)));
+
+                    (a, a1, a2, a3)
+                },
+            )
+            .unwrap();
+
+        // Run the indexing workload
+        world.run_default().unwrap();
+
+        // Delete the first and last siblings
+        world
+            .run(|mut vm_child_of: ViewMut<ChildOf>| {
+                vm_child_of.delete(a1);
+                vm_child_of.delete(a3);
+            })
+            .unwrap();
+
+        // Run the indexing workload
+        world.run_default().unwrap();
+
+        world
+            .run(
+                |v_parent_index: View<ParentIndex>, v_sibling_index: View<SiblingIndex>| {
+                    // The root should only have one child now
+                    let c: &ParentIndex = v_parent_index.get(a).expect("has a parent index");
+                    assert_eq!(parent_children_ids(c), vec![a2]);
+
+                    // The previously first and last siblings should no longer have an index
+                    assert_eq!(v_sibling_index.contains(a1), false);
+                    assert_eq!(v_sibling_index.contains(a3), false);
+
+                    // The previously middle node should now not have any siblings
+                    let a2_sib = v_sibling_index.get(a2).expect("should have sibling data");
+                    assert_eq!(a2_sib.prev_sibling, None, "only child");
+                    assert_eq!(a2_sib.next_sibling, None, "only child");
+                },
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn delete_middle_sibling() {
+        let world = setup_world_with_index_system();
+        let (a, a1, a2, a3) = world
+            .run(
+                |mut entities: EntitiesViewMut, mut vm_child_of: ViewMut<ChildOf>| {
+                    let a = entities.add_entity((), ());
+                    let a1 = entities.add_entity(&mut vm_child_of, ChildOf(a, Ordered::hinted(1)));
+                    let a2 = entities.add_entity(&mut vm_child_of, ChildOf(a, Ordered::hinted(2)));
+                    let a3 = entities.add_entity(&mut vm_child_of, ChildOf(a, Ordered::hinted(3)));
+
+                    (a, a1, a2, a3)
+                },
+            )
+            .unwrap();
+
+        // Run the indexing workload
+        world.run_default().unwrap();
+
+        // Delete the first and last siblings
+        world
+            .run(|mut vm_child_of: ViewMut<ChildOf>| {
+                vm_child_of.delete(a2);
+            })
+            .unwrap();
+
+        // Run the indexing workload
+        world.run_default().unwrap();
+
+        world
+            .run(
+                |v_parent_index: View<ParentIndex>, v_sibling_index: View<SiblingIndex>| {
+                    // The root should have 2 children now
+                    let c: &ParentIndex = v_parent_index.get(a).expect("has a parent index");
+                    assert_eq!(parent_children_ids(c), vec![a1, a3]);
+
+                    // The previously middle sibling should no longer have an index
+                    assert_eq!(v_sibling_index.contains(a2), false);
+
+                    // The previously first and last siblings should now be connected
+                    let a1_sib = v_sibling_index.get(a1).expect("should have sibling data");
+                    assert_eq!(a1_sib.next_sibling.unwrap().1, a3);
+
+                    let a3_sib = v_sibling_index.get(a3).expect("should have sibling data");
+                    assert_eq!(a3_sib.prev_sibling.unwrap().1, a1);
+                },
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn delete_middle_ancestor() {
+        let world = setup_world_with_index_system();
+        let (a, a1, a2) = world
+            .run(
+                |mut entities: EntitiesViewMut, mut vm_child_of: ViewMut<ChildOf>| {
+                    let a = entities.add_entity((), ());
+                    let a1 = entities.add_entity(&mut vm_child_of, ChildOf(a, Ordered::hinted(1)));
+                    let a2 = entities.add_entity(&mut vm_child_of, ChildOf(a1, Ordered::hinted(2)));
+
+                    (a, a1, a2)
+                },
+            )
+            .unwrap();
+
+        // Run the indexing workload
+        world.run_default().unwrap();
+
+        // Delete the first and last siblings
+        world
+            .run(|mut vm_child_of: ViewMut<ChildOf>| {
+                vm_child_of.delete(a1);
+            })
+            .unwrap();
+
+        // Run the indexing workload
+        world.run_default().unwrap();
+
+        // Only ChildOf was deleted, so the OrphanPolicy doesn't apply and a1 keeps its children
+        world
+            .run(
+                |v_parent_index: View<ParentIndex>, v_sibling_index: View<SiblingIndex>| {
+                    // The root now has no children
+                    let c: &ParentIndex = v_parent_index.get(a).expect("has a parent index");
+                    assert_eq!(parent_children_ids(c), vec![]);
+
+                    // The previous middle ancestor should no longer have a sibling index
+                    assert_eq!(v_parent_index.contains(a1), true);
+                    assert_eq!(v_sibling_index.contains(a1), false);
+
+                    // The previous grandchild still references its parent
+                    let a2_sib = v_sibling_index.get(a2).expect("should have sibling data");
+                    assert_eq!(a2_sib.parent_node, a1);
+                },
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn test_indexing() {
+        let app = App::new();
+        app.world
+            .add_unique(Events::<TreeViolation>::default())
+            .unwrap();
+        app.world.add_unique(OrphanPolicy::default()).unwrap();
+
+        WorkloadBuilder::new("default")
+            .with_system(indexing::tree_indexing::<Ordered>)
+            .with_system(|mut vm_child_of: ViewMut<ChildOf>| {
+                vm_child_of.clear_all_inserted_and_modified();
+            })
+            .add_to_world(&app.world)
+            .unwrap();
+
+        test_with_indexing_with_world(app);
+    }
+
+    fn app_with_plugin<P: Plugin + 'static>(plugin: P) -> App {
+        let app = App::new();
+        let mut builder = AppBuilder::new(&app);
+        builder.add_plugin(plugin);
+        builder.finish();
+        app
+    }
+
+    #[test]
+    fn test_indexing_with_plugin() {
+        let app = app_with_plugin(TreePlugin::default());
+        test_with_indexing_with_world(app);
+    }
+
+    fn test_with_indexing_with_world(app: App) {
+        let (a, a1, a2, a3, a6) = app.run(
+            |mut entities: EntitiesViewMut, mut vm_child_of: ViewMut<ChildOf>| {
+                let a = entities.add_entity((), ());
+                let a6 = entities.add_entity(&mut vm_child_of, ChildOf(a, Ordered::hinted(6)));
+                let a3 = entities.add_entity(&mut vm_child_of, ChildOf(a, Ordered::hinted(3)));
+                let a1 = entities.add_entity(&mut vm_child_of, ChildOf(a, Ordered::hinted(1)));
+                let a2 = entities.add_entity(&mut vm_child_of, ChildOf(a, Ordered::hinted(2)));
+                (a, a1, a2, a3, a6)
+            },
+        );
+
+        app.update();
+
+        app.run(
+            |v_parent_index: View<ParentIndex>, v_sibling_index: View<SiblingIndex>| {
+                v_sibling_index
+                    .get(a)
+                    .expect_err("should not have sibling data");
+
+                let c: &ParentIndex = v_parent_index.get(a).expect("has children");
+
+                assert_eq!(
+                    parent_children_ids(c),
+                    vec![a1, a2, a3, a6],
+                    "should be in order"
+                );
+            },
+        );
+
+        let (a1b, a1b1, a0, a4, a7) = app.run(
+            |mut entities: EntitiesViewMut, mut vm_child_of: ViewMut<ChildOf>| {
+                let a7 = entities.add_entity(&mut vm_child_of, ChildOf(a, Ordered::hinted(7)));
+                let a0 = entities.add_entity(&mut vm_child_of, ChildOf(a, Ordered::hinted(0)));
+                let a4 = entities.add_entity(&mut vm_child_of, ChildOf(a, Ordered::hinted(4)));
+                let a1b = entities.add_entity(&mut vm_child_of, ChildOf(a1, Ordered::hinted(4)));
+                let a1b1 = entities.add_entity(&mut vm_child_of, ChildOf(a1b, Ordered::hinted(1)));
+                (a1b, a1b1, a0, a4, a7)
+            },
+        );
+
+        app.update();
+
+        app.run(
+            |v_parent_index: View<ParentIndex>, v_sibling_index: View<SiblingIndex>| {
+                v_sibling_index
+                    .get(a)
+                    .expect_err("should not have sibling data");
+
+                let a1b_sib: &SiblingIndex =
+                    v_sibling_index.get(a1b).expect("should have sibling data");
+
+                assert_eq!(a1b_sib.next_sibling, None, "only child");
+                assert_eq!(a1b_sib.prev_sibling, None, "only child");
+
+                assert_eq!(
+                    parent_children_ids(v_parent_index.get(a1b).expect("has children")),
+                    vec![a1b1],
+                    "should have the one child"
+                );
+
+                assert_eq!(
+                    parent_children_ids(v_parent_index.get(a).expect("has children")),
+                    vec![a0, a1, a2, a3, a4, a6, a7],
+                    "should be in order"
+                );
+            },
+        );
+
+        // test removing and deleting ChildOf components
+
+        app.run(|mut vm_child_of: ViewMut<ChildOf>| {
+            // remove should not be used as it is untracked for whatever reason...
+            vm_child_of.delete(a7);
+            vm_child_of.delete(a4);
+            vm_child_of.delete(a0);
+            vm_child_of.delete(a1b);
+        });
+
+        app.update();
+
+        app.run(
+            |v_parent_index: View<ParentIndex>, v_sibling_index: View<SiblingIndex>| {
+                v_sibling_index
+                    .get(a1b)
+                    .expect_err("should not have sibling data");
+
+                assert_eq!(
+                    parent_children_ids(v_parent_index.get(a1b).expect("has children")),
+                    vec![a1b1],
+                    "should have the one child"
+                );
+
+                assert_eq!(
+                    parent_children_ids(v_parent_index.get(a).expect("has children")),
+                    vec![a1, a2, a3, a6],
+                    "should be in order without deleted entities"
+                );
+            },
+        );
+
+        // test inserting & overwriting after deleting
+
+        // a2 updated to ord 7, a1 child of a8
+        let (a8,) = app.run(
+            |mut entities: EntitiesViewMut, mut vm_child_of: ViewMut<ChildOf>| {
+                (&mut vm_child_of).get(a2).unwrap().1 = Ordered::hinted(7);
+                let a8 = entities.add_entity(&mut vm_child_of, ChildOf(a, Ordered::hinted(8)));
+                (&mut vm_child_of).get(a1).unwrap().0 = a8;
+
+                (a8,)
+            },
+        );
+
+        app.update();
+
+        app.run(|v_parent_index: View<ParentIndex>| {
+            assert_eq!(
+                parent_children_ids(v_parent_index.get(a).expect("has children")),
+                vec![a3, a6, a2, a8],
+                "should be in order with updated entities"
+            );
+            assert_eq!(
+                parent_children_ids(v_parent_index.get(a8).expect("has children")),
+                vec![a1],
+                "should have modified parent"
+            );
+        });
+    }
+
+    #[test]
+    fn test_reordering_with_plugin() {
+        let app = app_with_plugin(TreePlugin::default());
+
+        let (a, a1, a2, a3) = app.run(
+            |mut entities: EntitiesViewMut, mut vm_child_of: ViewMut<ChildOf>| {
+                let a = entities.add_entity((), ());
+                let a1 = entities.add_entity(&mut vm_child_of, ChildOf(a, Ordered::hinted(1)));
+                let a2 = entities.add_entity(&mut vm_child_of, ChildOf(a, Ordered::hinted(2)));
+                let a3 = entities.add_entity(&mut vm_child_of, ChildOf(a, Ordered::hinted(3)));
+                (a, a1, a2, a3)
+            },
+        );
+
+        app.update();
+
+        let move_and_update = |target: EntityId, place: MoveToPlace| {
+            app.run(|mut uvm_commands: UniqueViewMut<MoveCommands>| {
+                uvm_commands.push(MoveCmd::new(target, place));
+            });
+            app.update();
+            app.run(|v_parent_index: View<ParentIndex>| {
+                parent_children_ids(v_parent_index.get(a).expect("has children"))
+            })
+        };
+
+        assert_eq!(
+            move_and_update(a1, MoveToPlace::LastChildOf(a)),
+            vec![a2, a3, a1],
+            "moved to the end"
+        );
+        assert_eq!(
+            move_and_update(a3, MoveToPlace::FirstChildOf(a)),
+            vec![a3, a2, a1],
+            "moved to the start"
+        );
+        assert!(app.run(|uv_commands: UniqueView<MoveCommands>| uv_commands.is_empty()));
+    }
+
+    fn setup_app_with_children(count: u8) -> (App, EntityId, Vec<EntityId>) {
+        let app = app_with_plugin(TreePlugin::default());
+
+        let (a, children) = app.run(
+            |mut entities: EntitiesViewMut, mut vm_child_of: ViewMut<ChildOf>| {
+                let a = entities.add_entity((), ());
+                let children = (1..=count)
+                    .map(|hint| entities.add_entity(&mut vm_child_of, ChildOf::new(a, hint)))
+                    .collect::<Vec<_>>();
+                (a, children)
+            },
+        );
+        app.update();
+
+        (app, a, children)
+    }
+
+    fn move_all_and_update(app: &App, cmds: Vec<(EntityId, MoveToPlace)>) {
+        app.run(|mut uvm_commands: UniqueViewMut<MoveCommands>| {
+            for (target, place) in cmds {
+                uvm_commands.push(MoveCmd::new(target, place));
+            }
+        });
+        app.update();
+    }
+
+    /// Children of `parent`, checking the [SiblingIndex] links agree with the [ParentIndex]
+    fn indexed_children(app: &App, parent: EntityId) -> Vec<EntityId> {
+        app.run(
+            |v_parent_index: View<ParentIndex>, v_sibling_index: View<SiblingIndex>| {
+                let children = &v_parent_index.get(parent).expect("has children").children;
+                for (idx, child) in children.iter().enumerate() {
+                    let sibling = v_sibling_index.get(child.1).expect("has sibling data");
+                    assert_eq!(sibling.parent_node, parent);
+                    assert_eq!(sibling.ordered_node, *child);
+                    assert_eq!(
+                        sibling.prev_sibling,
+                        idx.checked_sub(1).map(|i| children[i])
+                    );
+                    assert_eq!(sibling.next_sibling, children.get(idx + 1).copied());
+                }
+                children.iter().map(|c| c.1).collect()
+            },
+        )
+    }
+
+    #[test]
+    fn test_reordering_chained_after_commands() {
+        let (app, a, children) = setup_app_with_children(4);
+        let (a1, a2, a3, a4) = (children[0], children[1], children[2], children[3]);
+
+        // a2 follows a1 wherever a1 was just moved
+        move_all_and_update(
+            &app,
+            vec![(a1, MoveToPlace::After(a3)), (a2, MoveToPlace::After(a1))],
+        );
+        assert_eq!(indexed_children(&app, a), vec![a3, a1, a2, a4]);
+
+        // a4 after a3, which itself was just moved after a2
+        move_all_and_update(
+            &app,
+            vec![(a3, MoveToPlace::After(a2)), (a4, MoveToPlace::After(a3))],
+        );
+        assert_eq!(indexed_children(&app, a), vec![a1, a2, a3, a4]);
+    }
+
+    #[test]
+    fn test_reordering_chained_first_and_last_commands() {
+        let (app, a, children) = setup_app_with_children(3);
+        let (a1, a2, a3) = (children[0], children[1], children[2]);
+
+        // each command sees the previous last child, instead of all landing after a3
+        move_all_and_update(
+            &app,
+            vec![
+                (a1, MoveToPlace::LastChildOf(a)),
+                (a2, MoveToPlace::LastChildOf(a)),
+            ],
+        );
+        assert_eq!(indexed_children(&app, a), vec![a3, a1, a2]);
+
+        move_all_and_update(
+            &app,
+            vec![
+                (a1, MoveToPlace::FirstChildOf(a)),
+                (a2, MoveToPlace::FirstChildOf(a)),
+            ],
+        );
+        assert_eq!(indexed_children(&app, a), vec![a2, a1, a3]);
+    }
+
+    #[test]
+    fn test_reordering_chained_across_parents() {
+        let (app, a, children) = setup_app_with_children(3);
+        let (a1, a2, a3) = (children[0], children[1], children[2]);
+
+        // a2 becomes a1's first child, then a3 follows a2 into a1, then a1 is unlinked and relinked
+        move_all_and_update(
+            &app,
+            vec![
+                (a2, MoveToPlace::FirstChildOf(a1)),
+                (a3, MoveToPlace::After(a2)),
+                (a1, MoveToPlace::Unlink),
+                (a1, MoveToPlace::LastChildOf(a)),
+            ],
+        );
+        assert_eq!(indexed_children(&app, a), vec![a1]);
+        assert_eq!(indexed_children(&app, a1), vec![a2, a3]);
+    }
+
+    #[test]
+    fn test_reordering_skips_moves_after_an_unlinked_entity() {
+        let (app, a, children) = setup_app_with_children(3);
+        let (a1, a2, a3) = (children[0], children[1], children[2]);
+
+        // a1 isn't a child anymore when a2 moves after it, a isn't a child at all, later commands still apply
+        move_all_and_update(
+            &app,
+            vec![
+                (a1, MoveToPlace::Unlink),
+                (a2, MoveToPlace::After(a1)),
+                (a3, MoveToPlace::After(a)),
+                (a3, MoveToPlace::FirstChildOf(a)),
+            ],
+        );
+        assert_eq!(indexed_children(&app, a), vec![a3, a2]);
+        assert!(!app.run(|v_child_of: View<ChildOf>| v_child_of.contains(a1)));
+        assert_eq!(
+            app.run(|uv_log: UniqueView<MoveLog>| uv_log.moves().len()),
+            2,
+            "skipped moves aren't logged"
+        );
+    }
+
+    #[test]
+    fn test_reordering_rebalances_exhausted_keys() {
+        let (app, a, children) = setup_app_with_children(2);
+        let (a1, a2) = (children[0], children[1]);
+
+        // every move lands right after a1, halving the same gap each time
+        let moved = app.run(|mut entities: EntitiesViewMut| {
+            (0..40)
+                .map(|_| entities.add_entity((), ()))
+                .collect::<Vec<_>>()
+        });
+        move_all_and_update(
+            &app,
+            moved
+                .iter()
+                .map(|target| (*target, MoveToPlace::After(a1)))
+                .collect(),
+        );
+
+        let mut expected = vec![a1];
+        expected.extend(moved.iter().rev());
+        expected.push(a2);
+        assert_eq!(indexed_children(&app, a), expected);
+
+        app.run(|v_child_of: View<ChildOf>| {
+            let keys = expected
+                .iter()
+                .map(|child| v_child_of.get(*child).unwrap().1)
+                .collect::<Vec<_>>();
+            assert!(
+                keys.windows(2).all(|pair| pair[0] < pair[1]),
+                "no siblings tie"
+            );
+        });
+    }
+
+    #[test]
+    fn test_reordering_fractional_index_without_rebalancing() {
+        let app = app_with_plugin(TreePlugin::<FractionalIndex>::with_order(ReplicaId(1)));
+
+        let (a, a1, a2, moved) = app.run(
+            |mut entities: EntitiesViewMut, mut vm_child_of: ViewMut<ChildOf<FractionalIndex>>| {
+                let a = entities.add_entity((), ());
+                let keys = FractionalIndex::n_between(None, None, 2);
+                let a1 = entities.add_entity(&mut vm_child_of, ChildOf(a, keys[0].clone()));
+                let a2 = entities.add_entity(&mut vm_child_of, ChildOf(a, keys[1].clone()));
+                let moved = (0..40)
+                    .map(|_| entities.add_entity((), ()))
+                    .collect::<Vec<_>>();
+                (a, a1, a2, moved)
+            },
+        );
+        app.update();
+
+        let keys = |app: &App, entities: &[EntityId]| {
+            app.run(|v_child_of: View<ChildOf<FractionalIndex>>| {
+                entities
+                    .iter()
+                    .map(|entity| v_child_of.get(*entity).unwrap().1.clone())
+                    .collect::<Vec<_>>()
+            })
+        };
+        let sibling_keys = keys(&app, &[a1, a2]);
+
+        app.run(
+            |mut uvm_commands: UniqueViewMut<MoveCommands<FractionalIndex>>| {
+                for target in &moved {
+                    uvm_commands.push(MoveCmd::new(*target, MoveToPlace::After(a1)));
+                }
+            },
+        );
+        app.update();
+
+        let mut expected = vec![a1];
+        expected.extend(moved.iter().rev());
+        expected.push(a2);
+        app.run(|v_parent_index: View<ParentIndex<FractionalIndex>>| {
+            let children = &v_parent_index.get(a).expect("has children").children;
+            assert_eq!(children.iter().map(|c| c.1).collect::<Vec<_>>(), expected);
+        });
+        assert_eq!(keys(&app, &[a1, a2]), sibling_keys, "never renumbered");
+    }
+
+    /// Root and 4 children, with the same ids and keys on every replica
+    fn setup_replica(replica: u64) -> (App, Vec<EntityId>) {
+        let app = app_with_plugin(TreePlugin::<FractionalIndex>::with_order(ReplicaId(
+            replica,
+        )));
+
+        let nodes = app.run(
+            |mut entities: EntitiesViewMut, mut vm_child_of: ViewMut<ChildOf<FractionalIndex>>| {
+                let root = entities.add_entity((), ());
+                let mut nodes = vec![root];
+                for key in FractionalIndex::n_between(None, None, 4) {
+                    nodes.push(entities.add_entity(&mut vm_child_of, ChildOf(root, key)));
+                }
+                nodes
+            },
+        );
+        app.update();
+
+        (app, nodes)
+    }
+
+    fn push_moves(app: &App, cmds: Vec<MoveCmd<FractionalIndex>>) {
+        app.run(
+            |mut uvm_commands: UniqueViewMut<MoveCommands<FractionalIndex>>| {
+                for cmd in cmds {
+                    uvm_commands.push(cmd);
+                }
+            },
+        );
+        app.update();
+    }
+
+    fn logged_commands(app: &App) -> Vec<MoveCmd<FractionalIndex>> {
+        app.run(|uv_log: UniqueView<MoveLog<FractionalIndex>>| uv_log.commands())
+    }
+
+    fn replica_state(
+        app: &App,
+        nodes: &[EntityId],
+    ) -> Vec<(Option<ChildOf<FractionalIndex>>, Vec<EntityId>)> {
+        app.run(
+            |v_child_of: View<ChildOf<FractionalIndex>>,
+             v_parent_index: View<ParentIndex<FractionalIndex>>| {
+                nodes
+                    .iter()
+                    .map(|node| {
+                        let children: Vec<EntityId> = v_parent_index
+                            .get(*node)
+                            .map(|parent_index| parent_index.children.iter().map(|c| c.1).collect())
+                            .unwrap_or_default();
+                        (v_child_of.get(*node).ok().cloned(), children)
+                    })
+                    .collect()
+            },
+        )
+    }
+
+    fn permutations(n: usize) -> Vec<Vec<usize>> {
+        if n == 0 {
+            return vec![Vec::new()];
+        }
+        permutations(n - 1)
+            .into_iter()
+            .flat_map(|permutation| {
+                (0..n).map(move |at| {
+                    let mut permutation = permutation.clone();
+                    permutation.insert(at, n - 1);
+                    permutation
+                })
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_concurrent_moves_converge_in_any_order() {
+        let (replica_1, nodes) = setup_replica(1);
+        let (replica_2, _) = setup_replica(2);
+        let (root, c1, c2, c3, c4) = (nodes[0], nodes[1], nodes[2], nodes[3], nodes[4]);
+
+        // concurrent moves, applying both c1 under c2 and c2 under c1 would make a loop
+        push_moves(
+            &replica_1,
+            vec![
+                MoveCmd::new(c1, MoveToPlace::FirstChildOf(c2)),
+                MoveCmd::new(c3, MoveToPlace::After(c4)),
+            ],
+        );
+        push_moves(
+            &replica_2,
+            vec![
+                MoveCmd::new(c2, MoveToPlace::FirstChildOf(c1)),
+                MoveCmd::new(c4, MoveToPlace::LastChildOf(c1)),
+            ],
+        );
+        let mut moves = logged_commands(&replica_1);
+        moves.extend(logged_commands(&replica_2));
+
+        // exchange logs, moves already applied are ignored
+        push_moves(&replica_1, logged_commands(&replica_2));
+        push_moves(&replica_2, logged_commands(&replica_1));
+        let expected = replica_state(&replica_1, &nodes);
+        assert_eq!(replica_state(&replica_2, &nodes), expected);
+
+        // c2 under c1 has the later timestamp of the looping moves, so it's skipped
+        let parent = |node: usize| expected[node].0.as_ref().map(|child_of| child_of.0);
+        assert_eq!(parent(1), Some(c2));
+        assert_eq!(parent(2), Some(root));
+        assert_eq!(parent(3), Some(root));
+        assert_eq!(parent(4), Some(c1));
+        assert_eq!(expected[0].1, vec![c2, c3]);
+        assert_eq!(expected[1].1, vec![c4]);
+        assert_eq!(expected[2].1, vec![c1]);
+
+        for order in permutations(moves.len()) {
+            let (replica, _) = setup_replica(3);
+            for index in &order {
+                push_moves(&replica, vec![moves[*index].clone()]);
+            }
+            assert_eq!(
+                replica_state(&replica, &nodes),
+                expected,
+                "applied in order {:?}",
+                order
+            );
+        }
+    }
+
+    #[test]
+    fn test_moves_after_tied_siblings_go_after_the_tie() {
+        let (replica_1, nodes) = setup_replica(1);
+        let (replica_2, _) = setup_replica(2);
+        let (root, c1, c2, c3, c4) = (nodes[0], nodes[1], nodes[2], nodes[3], nodes[4]);
+
+        // both replicas insert at the same spot, so c3 and c4 get the same key
+        push_moves(&replica_1, vec![MoveCmd::new(c3, MoveToPlace::After(c1))]);
+        push_moves(&replica_2, vec![MoveCmd::new(c4, MoveToPlace::After(c1))]);
+        push_moves(&replica_1, logged_commands(&replica_2));
+        push_moves(&replica_2, logged_commands(&replica_1));
+        let tied = replica_state(&replica_1, &nodes);
+        assert_eq!(tied[3].0, tied[4].0, "tied keys");
+        assert_eq!(tied[0].1, vec![c1, c3, c4, c2]);
+
+        // c4 shares c3's key, so c1 lands after both instead of renumbering the siblings
+        push_moves(&replica_1, vec![MoveCmd::new(c1, MoveToPlace::After(c3))]);
+        let moved = replica_state(&replica_1, &nodes);
+        assert_eq!(moved[0].1, vec![c3, c4, c1, c2]);
+        assert_eq!(moved[1].0.as_ref().map(|child_of| child_of.0), Some(root));
+        assert_eq!(&moved[2..], &tied[2..], "never renumbered");
+
+        push_moves(&replica_2, logged_commands(&replica_1));
+        assert_eq!(replica_state(&replica_2, &nodes), moved);
+    }
+
+    #[test]
+    fn test_remote_moves_skip_deleted_entities() {
+        let (app, nodes) = setup_replica(1);
+        let (root, c1, c2, c3, c4) = (nodes[0], nodes[1], nodes[2], nodes[3], nodes[4]);
+
+        push_moves(&app, vec![MoveCmd::new(c1, MoveToPlace::LastChildOf(c2))]);
+        app.run(|mut all_storages: AllStoragesViewMut| {
+            all_storages.delete_entity(c1);
+        });
+        app.update();
+
+        // arrives after the deletion, but is ordered before the local move, which is undone and redone around it
+        let remote = |counter: u64, target: EntityId, parent: EntityId| MoveCmd {
+            target,
+            place: MoveToPlace::To(ChildOf(parent, FractionalIndex::default())),
+            timestamp: Some(LamportTimestamp {
+                counter,
+                replica: ReplicaId(0),
+            }),
+        };
+        push_moves(
+            &app,
+            vec![remote(1, c3, c2), remote(2, c4, c1), remote(3, c1, root)],
+        );
+
+        let skipped = app.run(|uv_log: UniqueView<MoveLog<FractionalIndex>>| {
+            uv_log
+                .moves()
+                .iter()
+                .map(|logged| (logged.target, logged.skipped))
+                .collect::<Vec<_>>()
+        });
+        assert_eq!(
+            skipped,
+            vec![(c3, false), (c1, true), (c4, true), (c1, true)]
+        );
+        let state = replica_state(&app, &nodes);
+        assert_eq!(state[0].1, vec![c2, c4]);
+        assert_eq!(state[2].1, vec![c3]);
+    }
+
+    #[test]
+    fn test_moves_with_a_taken_timestamp_are_dropped() {
+        let (replica_1, nodes) = setup_replica(0);
+        let (replica_2, _) = setup_replica(0);
+        let (c1, c2, c3) = (nodes[1], nodes[2], nodes[3]);
+
+        push_moves(
+            &replica_1,
+            vec![MoveCmd::new(c1, MoveToPlace::LastChildOf(c2))],
+        );
+        push_moves(
+            &replica_2,
+            vec![MoveCmd::new(c3, MoveToPlace::LastChildOf(c2))],
+        );
+        push_moves(&replica_1, logged_commands(&replica_2));
+
+        // both moves were stamped with the same counter and replica, so replica 1 keeps its own
+        assert_eq!(logged_commands(&replica_1).len(), 1);
+        assert_eq!(replica_state(&replica_1, &nodes)[2].1, vec![c1]);
+    }
+
+    #[test]
+    fn test_moves_before_the_trimmed_ones_are_dropped() {
+        let (replica_1, nodes) = setup_replica(1);
+        let (replica_2, _) = setup_replica(2);
+        let (c1, c2, c3, c4) = (nodes[1], nodes[2], nodes[3], nodes[4]);
+
+        push_moves(
+            &replica_1,
+            vec![
+                MoveCmd::new(c1, MoveToPlace::LastChildOf(c2)),
+                MoveCmd::new(c4, MoveToPlace::LastChildOf(c2)),
+            ],
+        );
+        push_moves(
+            &replica_2,
+            vec![MoveCmd::new(c3, MoveToPlace::LastChildOf(c2))],
+        );
+
+        replica_1.run(|mut uvm_log: UniqueViewMut<MoveLog<FractionalIndex>>| {
+            let latest = uvm_log.latest().expect("logged moves");
+            uvm_log.trim(latest);
+        });
+        assert!(logged_commands(&replica_1).is_empty());
+
+        // replica 2's move is ordered before the trimmed moves, which can't be undone anymore
+        push_moves(&replica_1, logged_commands(&replica_2));
+        assert!(logged_commands(&replica_1).is_empty());
+        assert_eq!(replica_state(&replica_1, &nodes)[2].1, vec![c1, c4]);
+
+        // local moves are still stamped after the trimmed ones
+        push_moves(
+            &replica_1,
+            vec![MoveCmd::new(c3, MoveToPlace::FirstChildOf(c2))],
+        );
+        assert_eq!(logged_commands(&replica_1).len(), 1);
+        assert_eq!(replica_state(&replica_1, &nodes)[2].1, vec![c3, c1, c4]);
+    }
+
+    fn violations(app: &App) -> Vec<TreeViolation> {
+        app.run(|uv_violations: UniqueView<Events<TreeViolation>>| {
+            uv_violations.iter().cloned().collect()
+        })
+    }
+
+    fn rejected(app: &App, entity: EntityId) -> Option<ChildOf> {
+        app.run(|v_rejected_child_of: View<RejectedChildOf>| {
+            v_rejected_child_of
+                .get(entity)
+                .ok()
+                .map(|rejected| rejected.0.clone())
+        })
+    }
+
+    #[test]
+    fn test_rejects_self_parent() {
+        let (app, a, children) = setup_app_with_children(1);
+        let a1 = children[0];
+
+        let (b,) = app.run(
+            |mut entities: EntitiesViewMut, mut vm_child_of: ViewMut<ChildOf>| {
+                let b = entities.add_entity((), ());
+                entities.add_component(b, &mut vm_child_of, ChildOf::new(b, 1));
+                (b,)
+            },
+        );
+        app.update();
+
+        assert_eq!(rejected(&app, b), Some(ChildOf::new(b, 1)));
+        assert_eq!(
+            violations(&app),
+            vec![TreeViolation {
+                entity: b,
+                rejected: ChildOf::new(b, 1),
+                ancestors: vec![b],
+            }]
+        );
+        app.run(
+            |v_child_of: View<ChildOf>, v_parent_index: View<ParentIndex>| {
+                assert!(!v_child_of.contains(b));
+                assert!(!v_parent_index.contains(b));
+            },
+        );
+        assert_eq!(indexed_children(&app, a), vec![a1], "unaffected");
+    }
+
+    #[test]
+    fn test_rejects_modified_ancestor_loop() {
+        let (app, a, children) = setup_app_with_children(2);
+        let (a1, a2) = (children[0], children[1]);
+
+        // a1 -> a2 -> a1
+        app.run(|mut vm_child_of: ViewMut<ChildOf>| {
+            (&mut vm_child_of).get(a2).unwrap().0 = a1;
+        });
+        app.update();
+        assert!(violations(&app).is_empty());
+        assert_eq!(indexed_children(&app, a1), vec![a2]);
+
+        app.run(|mut vm_child_of: ViewMut<ChildOf>| {
+            (&mut vm_child_of).get(a1).unwrap().0 = a2;
+        });
+        app.update();
+
+        let violations = violations(&app);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].entity, a1);
+        assert_eq!(violations[0].ancestors, vec![a2, a1]);
+        assert_eq!(rejected(&app, a1), Some(ChildOf::new(a2, 1)));
+
+        // a1 is unlinked from its previous parent, a2 stays its child
+        assert_eq!(indexed_children(&app, a), Vec::<EntityId>::new());
+        assert_eq!(indexed_children(&app, a1), vec![a2]);
+    }
+
+    #[test]
+    fn test_skips_move_into_own_descendant() {
+        let (app, a, children) = setup_app_with_children(2);
+        let (a1, a2) = (children[0], children[1]);
+
+        move_all_and_update(&app, vec![(a, MoveToPlace::LastChildOf(a2))]);
+
+        // the move log skips the move, so there is nothing to reject
+        assert!(violations(&app).is_empty());
+        assert_eq!(rejected(&app, a), None);
+        assert!(app.run(|uv_log: UniqueView<MoveLog>| uv_log.moves()[0].skipped));
+        assert!(!app.run(|v_child_of: View<ChildOf>| v_child_of.contains(a)));
+        assert_eq!(indexed_children(&app, a), vec![a1, a2]);
+    }
+
+    #[test]
+    fn test_rejects_one_of_mutual_parents() {
+        let (app, _, _) = setup_app_with_children(0);
+
+        let (b, c) = app.run(
+            |mut entities: EntitiesViewMut, mut vm_child_of: ViewMut<ChildOf>| {
+                let b = entities.add_entity((), ());
+                let c = entities.add_entity(&mut vm_child_of, ChildOf::new(b, 1));
+                entities.add_component(b, &mut vm_child_of, ChildOf::new(c, 1));
+                (b, c)
+            },
+        );
+        app.update();
+
+        let violations = violations(&app);
+        assert_eq!(violations.len(), 1, "rejecting one ChildOf breaks the loop");
+        let (rejected_entity, kept_entity) = if violations[0].entity == b {
+            (b, c)
+        } else {
+            (c, b)
+        };
+        assert!(rejected(&app, rejected_entity).is_some());
+        assert_eq!(indexed_children(&app, rejected_entity), vec![kept_entity]);
+    }
+
+    /// a -> [a1, b], a1 -> [a2, a3], a2 -> [a21]
+    fn setup_app_with_grandchildren(orphan_policy: OrphanPolicy) -> (App, [EntityId; 6]) {
+        let app = app_with_plugin(TreePlugin::default().with_orphan_policy(orphan_policy));
+
+        let entities = app.run(
+            |mut entities: EntitiesViewMut, mut vm_child_of: ViewMut<ChildOf>| {
+                let a = entities.add_entity((), ());
+                let a1 = entities.add_entity(&mut vm_child_of, ChildOf::new(a, 1));
+                let b = entities.add_entity(&mut vm_child_of, ChildOf::new(a, 2));
+                let a2 = entities.add_entity(&mut vm_child_of, ChildOf::new(a1, 1));
+                let a3 = entities.add_entity(&mut vm_child_of, ChildOf::new(a1, 2));
+                let a21 = entities.add_entity(&mut vm_child_of, ChildOf::new(a2, 1));
+                [a, a1, b, a2, a3, a21]
+            },
+        );
+        app.update();
+
+        (app, entities)
+    }
+
+    fn delete_entity_and_update(app: &App, entity: EntityId) {
+        app.run(|mut all_storages: AllStoragesViewMut| {
+            all_storages.delete_entity(entity);
+        });
+        app.update();
+    }
+
+    fn is_alive(app: &App, entity: EntityId) -> bool {
+        app.run(|v_entities: EntitiesView| v_entities.is_alive(entity))
+    }
+
+    #[test]
+    fn delete_middle_ancestor_cascade_delete() {
+        let (app, [a, a1, b, a2, a3, a21]) =
+            setup_app_with_grandchildren(OrphanPolicy::CascadeDelete);
+
+        delete_entity_and_update(&app, a1);
+
+        assert_eq!(indexed_children(&app, a), vec![b]);
+        for descendant in [a2, a3, a21].iter() {
+            assert!(!is_alive(&app, *descendant));
+        }
+
+        // the deleted descendants' ChildOf are cleaned up without touching the rest of the tree
+        app.update();
+        assert_eq!(indexed_children(&app, a), vec![b]);
+    }
+
+    #[test]
+    fn delete_middle_ancestor_reparent_to_grandparent() {
+        let (app, [a, a1, b, a2, a3, a21]) =
+            setup_app_with_grandchildren(OrphanPolicy::ReparentToGrandparent);
+
+        delete_entity_and_update(&app, a1);
+
+        // a1's children take its place, before b
+        assert_eq!(indexed_children(&app, a), vec![a2, a3, b]);
+        assert_eq!(indexed_children(&app, a2), vec![a21]);
+        app.run(|v_child_of: View<ChildOf>| {
+            assert_eq!(v_child_of.get(a2).unwrap().0, a);
+            assert_eq!(v_child_of.get(a3).unwrap().0, a);
+        });
+    }
+
+    #[test]
+    fn delete_middle_ancestor_detach_to_root() {
+        let (app, [a, a1, b, a2, a3, a21]) =
+            setup_app_with_grandchildren(OrphanPolicy::DetachToRoot);
+
+        delete_entity_and_update(&app, a1);
+
+        assert_eq!(indexed_children(&app, a), vec![b]);
+        app.run(
+            |v_child_of: View<ChildOf>, v_sibling_index: View<SiblingIndex>| {
+                for root in [a2, a3].iter() {
+                    assert!(!v_child_of.contains(*root));
+                    assert!(!v_sibling_index.contains(*root));
+                }
+            },
+        );
+        // the new roots keep their own children
+        assert_eq!(indexed_children(&app, a2), vec![a21]);
+    }
+
+    fn parent_children_ids(pi: &ParentIndex) -> Vec<EntityId> {
+        pi.children.iter().map(|c| c.1).collect()
+    }
+}
@@ -0,0 +1,174 @@
+/// Variable length ordering key which always has room for another key between any two, see [SiblingOrder](super::SiblingOrder).
+///
+/// The bytes are the base 256 digits of a fraction in `(0, 1)`, so keys sort lexicographically.
+/// A key never ends with a zero byte, which is what guarantees there is always a key in between.
+///
+/// Keys grow by about one byte for every 8 inserts at the same spot, but moving an entity never touches its siblings'
+/// keys, so [ChildOf](super::ChildOf)s can be synced between peers without renumbering passes.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FractionalIndex(Vec<u8>);
+
+impl Default for FractionalIndex {
+    /// The key half way through the range
+    fn default() -> Self {
+        FractionalIndex(vec![0x80])
+    }
+}
+
+impl FractionalIndex {
+    /// Restore a key from [FractionalIndex::as_bytes], `None` if the bytes are empty or end with a zero byte
+    pub fn from_bytes(bytes: Vec<u8>) -> Option<Self> {
+        match bytes.last() {
+            Some(last) if *last != 0 => Some(FractionalIndex(bytes)),
+            _ => None,
+        }
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    pub fn between(min: &Self, max: &Self) -> Self {
+        assert!(min < max, "Min key must be less than max key");
+        FractionalIndex(midpoint(&min.0, Some(&max.0)))
+    }
+
+    pub fn after(&self) -> Self {
+        FractionalIndex(midpoint(&self.0, None))
+    }
+
+    pub fn before(&self) -> Self {
+        FractionalIndex(midpoint(&[], Some(&self.0)))
+    }
+
+    /// `n` ascending keys between `min` and `max`, either end being open when `None`.
+    ///
+    /// Keys are split evenly by bisection, so they stay as short as possible.
+    pub fn n_between(min: Option<&Self>, max: Option<&Self>, n: usize) -> Vec<Self> {
+        if let (Some(min), Some(max)) = (min, max) {
+            assert!(min < max, "Min key must be less than max key");
+        }
+        let mut keys = Vec::with_capacity(n);
+        push_n_between(
+            &mut keys,
+            min.map(|min| min.0.as_slice()).unwrap_or(&[]),
+            max.map(|max| max.0.as_slice()),
+            n,
+        );
+        keys
+    }
+}
+
+fn push_n_between(keys: &mut Vec<FractionalIndex>, min: &[u8], max: Option<&[u8]>, n: usize) {
+    if n == 0 {
+        return;
+    }
+    let mid = midpoint(min, max);
+    let before = n / 2;
+    push_n_between(keys, min, Some(&mid), before);
+    keys.push(FractionalIndex(mid.clone()));
+    push_n_between(keys, &mid, max, n - before - 1);
+}
+
+/// Shortest digits strictly between `min` and `max` (`None` being one past the last key).
+///
+/// Both must be free of trailing zeros, an empty `min` being the start of the range.
+fn midpoint(min: &[u8], max: Option<&[u8]>) -> Vec<u8> {
+    let digit = |digits: &[u8], i: usize| digits.get(i).copied().unwrap_or(0);
+
+    if let Some(max) = max {
+        // keep the common prefix, treating missing digits of min as zeros
+        let prefix = (0..max.len())
+            .take_while(|i| digit(min, *i) == max[*i])
+            .count();
+        if prefix > 0 {
+            let mut digits = max[..prefix].to_vec();
+            digits.extend(midpoint(
+                min.get(prefix..).unwrap_or(&[]),
+                Some(&max[prefix..]),
+            ));
+            return digits;
+        }
+    }
+
+    let min_digit = digit(min, 0) as u16;
+    let max_digit = max.map(|max| max[0] as u16).unwrap_or(256);
+    if max_digit - min_digit > 1 {
+        vec![((min_digit + max_digit) / 2) as u8]
+    } else {
+        match max {
+            // max has more digits, so its first digit alone sorts before it
+            Some(max) if max.len() > 1 => vec![max[0]],
+            // no room in this digit, find room in the next one
+            _ => {
+                let mut digits = vec![min_digit as u8];
+                digits.extend(midpoint(min.get(1..).unwrap_or(&[]), None));
+                digits
+            }
+        }
+    }
+}
+
+#[test]
+fn between_sorts_strictly_between() {
+    let a = FractionalIndex::default();
+    let b = a.after();
+    let mid = FractionalIndex::between(&a, &b);
+    assert!(a < mid && mid < b);
+
+    // adjacent digits need another byte
+    let a = FractionalIndex::from_bytes(vec![0x10]).unwrap();
+    let b = FractionalIndex::from_bytes(vec![0x11]).unwrap();
+    let mid = FractionalIndex::between(&a, &b);
+    assert_eq!(mid.as_bytes(), &[0x10, 0x80]);
+
+    // a prefix of max is less than max
+    let b = FractionalIndex::from_bytes(vec![0x11, 0x01]).unwrap();
+    let a = FractionalIndex::from_bytes(vec![0x10, 0xff]).unwrap();
+    let mid = FractionalIndex::between(&a, &b);
+    assert!(a < mid && mid < b, "{:?} < {:?} < {:?}", a, mid, b);
+}
+
+#[test]
+fn repeated_inserts_at_the_same_spot_never_run_out() {
+    let min = FractionalIndex::default();
+    let mut max = min.after();
+    for _ in 0..1000 {
+        let mid = FractionalIndex::between(&min, &max);
+        assert!(min < mid && mid < max);
+        assert_ne!(mid.as_bytes().last(), Some(&0));
+        max = mid;
+    }
+    assert!(max.as_bytes().len() < 200, "grows slowly");
+
+    let mut first = FractionalIndex::default();
+    let mut last = FractionalIndex::default();
+    for _ in 0..1000 {
+        let before = first.before();
+        let after = last.after();
+        assert!(before < first && last < after);
+        first = before;
+        last = after;
+    }
+}
+
+#[test]
+fn n_between_produces_ascending_keys() {
+    let keys = FractionalIndex::n_between(None, None, 100);
+    assert_eq!(keys.len(), 100);
+    assert!(keys.windows(2).all(|pair| pair[0] < pair[1]));
+    assert!(keys.iter().all(|key| key.as_bytes().len() <= 2));
+
+    let min = FractionalIndex::from_bytes(vec![0x10]).unwrap();
+    let max = FractionalIndex::from_bytes(vec![0x11]).unwrap();
+    let keys = FractionalIndex::n_between(Some(&min), Some(&max), 3);
+    assert_eq!(keys.len(), 3);
+    assert!(min < keys[0] && keys[2] < max);
+    assert!(keys.windows(2).all(|pair| pair[0] < pair[1]));
+}
+
+#[test]
+fn from_bytes_rejects_keys_without_room_before_them() {
+    assert_eq!(FractionalIndex::from_bytes(vec![]), None);
+    assert_eq!(FractionalIndex::from_bytes(vec![0x10, 0x00]), None);
+}
@@ -0,0 +1,348 @@
+use super::*;
+
+// Order first in tuple so it takes ordering precedence
+type SiblingID<O> = (O, EntityId);
+
+/// Managed by the tree_indexing system to provide more concise info for walking the tree
+#[derive(Debug, Component)]
+pub struct SiblingIndex<O: SiblingOrder = Ordered> {
+    pub parent_node: EntityId,
+    pub ordered_node: SiblingID<O>,
+    pub prev_sibling: Option<SiblingID<O>>,
+    pub next_sibling: Option<SiblingID<O>>,
+}
+
+/// Managed by the tree_indexing system to provide more concise info for walking the tree
+///
+/// Deletions are tracked, so the children left behind by a deleted entity can be handled by its [OrphanPolicy].
+#[derive(Debug, Component)]
+#[track(Deletion)]
+pub struct ParentIndex<O: SiblingOrder = Ordered> {
+    pub children: Vec<SiblingID<O>>,
+}
+
+/// Indexes tree [ChildOf] and [Ordering] components into more helpful between nodes
+///
+/// [ChildOf]s which would make an entity its own ancestor are moved to [RejectedChildOf] instead,
+/// and reported as [TreeViolation] events.
+///
+/// The children of deleted entities are handled according to the [OrphanPolicy] unique.
+pub fn tree_indexing<O: SiblingOrder>(
+    (
+        v_entities,
+        uv_orphan_policy,
+        commands,
+        mut vm_child_of,
+        mut vm_rejected_child_of,
+        mut vm_sibling_index,
+        mut vm_parent_index,
+        mut violations,
+    ): (
+        EntitiesView,
+        UniqueView<OrphanPolicy>,
+        Commands,
+        ViewMut<ChildOf<O>>,
+        ViewMut<RejectedChildOf<O>>,
+        ViewMut<SiblingIndex<O>>,
+        ViewMut<ParentIndex<O>>,
+        EventWriter<TreeViolation<O>>,
+    ),
+) {
+    // iff ChildOf was completely deleted (does not include "removed")
+    let deleted_child_of = vm_child_of.take_deleted();
+    for (deleted_id, ChildOf(parent_id, _)) in &deleted_child_of {
+        unlink_deleted_child(
+            &mut vm_sibling_index,
+            &mut vm_parent_index,
+            *deleted_id,
+            *parent_id,
+        );
+    }
+
+    // iff a parent entity was deleted, along with its ParentIndex
+    for deleted_parent in vm_parent_index.take_deleted() {
+        orphans::adopt_orphans(
+            *uv_orphan_policy,
+            &commands,
+            &v_entities,
+            (
+                &mut vm_child_of,
+                &mut vm_sibling_index,
+                &mut vm_parent_index,
+            ),
+            &deleted_child_of,
+            deleted_parent,
+        );
+    }
+
+    violation::reject_ancestor_loops(
+        &v_entities,
+        &mut vm_child_of,
+        &mut vm_rejected_child_of,
+        &mut vm_sibling_index,
+        &mut vm_parent_index,
+        &mut violations,
+    );
+
+    // iff ChildOf is completely new component
+    vm_child_of.inserted().iter().with_id().for_each(
+        |(inserted_id, ChildOf(parent_id, child_order))| {
+            insert_child_of(
+                &v_entities,
+                &vm_child_of,
+                &mut vm_sibling_index,
+                &mut vm_parent_index,
+                inserted_id,
+                &child_order,
+                *parent_id,
+            );
+        },
+    );
+
+    // iff ChildOf was modified
+    vm_child_of.modified().iter().ids().for_each(|modified_id| {
+        reindex_child(
+            &v_entities,
+            &vm_child_of,
+            &mut vm_sibling_index,
+            &mut vm_parent_index,
+            modified_id,
+        );
+    });
+
+    vm_child_of.clear_all_inserted_and_modified();
+}
+
+/// Move `child_id` in the indexes to wherever its [ChildOf] currently points.
+///
+/// Unlinked children (pointing at [EntityId::dead]) are only removed from their previous parent.
+pub(super) fn reindex_child<O: SiblingOrder>(
+    v_entities: &EntitiesView,
+    vm_child_of: &ViewMut<ChildOf<O>>,
+    vm_sibling_index: &mut ViewMut<SiblingIndex<O>>,
+    vm_parent_index: &mut ViewMut<ParentIndex<O>>,
+    child_id: EntityId,
+) {
+    // remove from parent
+    if vm_sibling_index.contains(child_id) {
+        unlink_child(vm_sibling_index, vm_parent_index, child_id);
+    }
+
+    // reinsert child
+    if let Ok(ChildOf(parent_id, child_order)) = vm_child_of.get(child_id) {
+        if *parent_id != EntityId::dead() {
+            insert_child_of(
+                v_entities,
+                vm_child_of,
+                vm_sibling_index,
+                vm_parent_index,
+                child_id,
+                child_order,
+                *parent_id,
+            );
+        }
+    }
+}
+
+fn insert_child_of<O: SiblingOrder>(
+    v_entities: &EntitiesView,
+    all_child_of_iter: &ViewMut<ChildOf<O>>, // needed for creating parent node indexes, since parents do not need a ChildOf component
+    vm_sibling_index: &mut ViewMut<SiblingIndex<O>>,
+    vm_parent_index: &mut ViewMut<ParentIndex<O>>,
+    child_id: EntityId,
+    child_order: &O,     // used to position between siblings
+    parent_id: EntityId, // insert to this parent
+) {
+    // parent: insert into list at correct location,
+    // find next index and previous index and update their sibling references respectively
+    let parent_index = {
+        if let Ok(parent_index) = vm_parent_index.get(parent_id) {
+            parent_index
+        } else {
+            let mut children = all_child_of_iter
+                .iter()
+                .with_id()
+                .filter(|(_, ChildOf(ref child_parent_id, _))| child_parent_id == &parent_id)
+                .map(|(id, ChildOf(_, ref ordered))| -> SiblingID<O> { (ordered.clone(), id) })
+                .collect::<Vec<SiblingID<O>>>();
+
+            children.sort();
+
+            // we need to create their SiblingIndex components
+            for (idx, child) in children.iter().enumerate() {
+                // dbg!(child);
+                v_entities.add_component(
+                    child.1,
+                    &mut *vm_sibling_index,
+                    SiblingIndex {
+                        next_sibling: if idx < children.len() - 1 {
+                            Some(children[idx + 1].clone())
+                        } else {
+                            None
+                        },
+                        prev_sibling: if idx > 0 {
+                            Some(children[idx - 1].clone())
+                        } else {
+                            None
+                        },
+                        ordered_node: child.clone(),
+                        parent_node: parent_id,
+                    },
+                );
+            }
+
+            // Good debugging spot if needed
+            // for sibling in vm_sibling_index.iter() {
+            //     dbg!(sibling);
+            // }
+
+            // parent has no parent or siblings
+            v_entities.add_component(parent_id, &mut *vm_parent_index, ParentIndex { children });
+
+            vm_parent_index
+                .get(parent_id)
+                .expect("parent should have a parent index now")
+        }
+    };
+
+    let siblings = &mut parent_index.children;
+
+    let to_insert: SiblingID<O> = (child_order.clone(), child_id);
+    if siblings.binary_search(&to_insert).is_err() {
+        // didn't find the sibling_id (ord + id) combo in siblings,
+        // this could mean that either the Ordered value changed, or
+        // this could mean that the entity is not present in the sibling list
+        // at all.
+
+        // remove our id, just in case it was just an "Ordered" change
+        siblings.retain(|(_, id)| id != &child_id);
+
+        // "insert_at" points to the index of the element after
+        // "insert_at - 1" points to the index of the previous element
+        let insert_at = {
+            siblings
+                .binary_search(&to_insert)
+                .expect_err("existing child")
+        };
+
+        let (prev_node_opt, next_node_opt) = {
+            (
+                if insert_at > 0 {
+                    // we have an element before to update (which becomes our previous node)
+                    Some((&siblings)[insert_at - 1].clone())
+                } else {
+                    None
+                },
+                if insert_at < siblings.len() {
+                    // we have an element after to update (which becomes our next node)
+                    Some((&siblings)[insert_at].clone())
+                } else {
+                    None
+                },
+            )
+        };
+
+        // insert node into children as final modification to siblings
+        siblings.insert(insert_at, to_insert.clone());
+
+        // update references
+        if let Some(prev_node) = &prev_node_opt {
+            // prev node should point at inserted node as next
+            (vm_sibling_index.get(prev_node.1).unwrap()).next_sibling = Some(to_insert.clone());
+        }
+
+        if let Some(next_node) = &next_node_opt {
+            // next node should point at inserted node as prev
+            (vm_sibling_index.get(next_node.1).unwrap()).prev_sibling = Some(to_insert.clone());
+        }
+
+        v_entities.add_component(
+            child_id,
+            vm_sibling_index,
+            SiblingIndex {
+                ordered_node: to_insert,
+                next_sibling: next_node_opt,
+                prev_sibling: prev_node_opt,
+                parent_node: parent_id,
+            },
+        );
+    }
+}
+
+/// Unlink a child whose [ChildOf] was deleted, its [SiblingIndex] may be gone already if the whole entity was deleted
+fn unlink_deleted_child<O: SiblingOrder>(
+    vm_sibling_index: &mut ViewMut<SiblingIndex<O>>,
+    vm_parent_index: &mut ViewMut<ParentIndex<O>>,
+    child: EntityId,
+    parent_id: EntityId,
+) {
+    if vm_sibling_index.contains(child) {
+        unlink_child(vm_sibling_index, vm_parent_index, child);
+        return;
+    }
+
+    // find the neighbours through the parent instead
+    let (prev_sibling, next_sibling) = match vm_parent_index.get(parent_id) {
+        Ok(mut parent_index) => {
+            let siblings = &mut parent_index.children;
+            match siblings.iter().position(|(_, id)| id == &child) {
+                Some(position) => {
+                    siblings.remove(position);
+                    (
+                        position.checked_sub(1).map(|prev| siblings[prev].clone()),
+                        siblings.get(position).cloned(),
+                    )
+                }
+                None => return,
+            }
+        }
+        // the parent was deleted too
+        Err(_) => return,
+    };
+
+    // neighbours deleted in the same update have no index left to update
+    if let Some(prev_sibling_id) = &prev_sibling {
+        if let Ok(mut prev_sibling_index) = vm_sibling_index.get(prev_sibling_id.1) {
+            prev_sibling_index.next_sibling = next_sibling.clone();
+        }
+    }
+
+    if let Some(next_sibling_id) = &next_sibling {
+        if let Ok(mut next_sibling_index) = vm_sibling_index.get(next_sibling_id.1) {
+            next_sibling_index.prev_sibling = prev_sibling;
+        }
+    }
+}
+
+pub(super) fn unlink_child<O: SiblingOrder>(
+    vm_sibling_index: &mut ViewMut<SiblingIndex<O>>,
+    vm_parent_index: &mut ViewMut<ParentIndex<O>>,
+    child: EntityId,
+) {
+    let (parent_id, t_prev_sibling, t_next_sibling) = {
+        let child_index = vm_sibling_index.get(child).unwrap();
+        (
+            child_index.parent_node,
+            child_index.prev_sibling.clone(),
+            child_index.next_sibling.clone(),
+        )
+    };
+
+    // parent: remove T from children
+    let parent_index = vm_parent_index.get(parent_id).unwrap();
+    parent_index.children.retain(|(_, id)| id != &child);
+
+    if let Some(prev_sibling_id) = &t_prev_sibling {
+        // prevsibling: set nextsibling to T's nextsibling
+        let mut prev_sibling_index = vm_sibling_index.get(prev_sibling_id.1).unwrap();
+        prev_sibling_index.next_sibling = t_next_sibling.clone();
+    }
+
+    if let Some(next_sibling_id) = t_next_sibling {
+        // nextsibling: set prevsibling to T's prevsibling
+        let mut next_sibling_index = vm_sibling_index.get(next_sibling_id.1).unwrap();
+        next_sibling_index.prev_sibling = t_prev_sibling;
+    }
+
+    vm_sibling_index.delete(child);
+}
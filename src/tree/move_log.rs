@@ -0,0 +1,279 @@
+use super::*;
+use tracing::*;
+
+/// Identifies a replica among the peers syncing a tree, see [MoveLog].
+///
+/// Moves are identified by their [LamportTimestamp], so peers sharing a replica id drop each other's moves.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ReplicaId(pub u64);
+
+/// Lamport timestamp of a move, totally ordering moves across replicas.
+///
+/// Compares the counter first, then the replica to break ties between concurrent moves.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct LamportTimestamp {
+    pub counter: u64,
+    pub replica: ReplicaId,
+}
+
+/// A move applied by [tree_reordering], with the [ChildOf] it replaced so it can be undone
+#[derive(Clone, Debug, PartialEq)]
+pub struct LoggedMove<O: SiblingOrder = Ordered> {
+    pub timestamp: LamportTimestamp,
+    pub target: EntityId,
+    /// Where the move placed the target
+    pub child_of: ChildOf<O>,
+    /// The target's [ChildOf] before the move, `None` if it had none
+    pub previous: Option<ChildOf<O>>,
+    /// Moves making the target its own ancestor, or involving an entity which isn't alive here, are logged, but skipped
+    pub skipped: bool,
+}
+
+/// Every move applied to the tree, in [LamportTimestamp] order.
+///
+/// A move arriving out of order undoes the moves logged after it, is applied, then the undone moves are redone.
+/// So replicas applying the same moves in any order end up with the same [ChildOf]s, as long as [ChildOf]s are only
+/// changed through [MoveCommands].
+///
+/// These changes bypass the log, so replicas diverge once they happen:
+///  - rebalancing [Ordered] sibling keys which ran out of room, use [FractionalIndex] keys for synced trees.
+///    They always have room between two different keys, and siblings sharing a key (placed at the same spot
+///    by different replicas) are never split, moves after one of them go after all of them
+///  - handling the children of a deleted entity with the [OrphanPolicy], deletions aren't synced either
+///  - moving [ChildOf]s creating an ancestor loop to [RejectedChildOf], when they weren't added through a move
+///
+/// Share [MoveLog::commands] with the other replicas, and push theirs into [MoveCommands].
+/// The log keeps every move until it's trimmed with [MoveLog::trim].
+#[derive(Component, Debug)]
+pub struct MoveLog<O: SiblingOrder = Ordered> {
+    replica: ReplicaId,
+    counter: u64,
+    moves: Vec<LoggedMove<O>>,
+    /// Latest timestamp passed to [MoveLog::trim]
+    trimmed: Option<LamportTimestamp>,
+}
+
+impl<O: SiblingOrder> MoveLog<O> {
+    pub fn new(replica: ReplicaId) -> Self {
+        MoveLog {
+            replica,
+            counter: 0,
+            moves: Vec::new(),
+            trimmed: None,
+        }
+    }
+
+    pub fn replica(&self) -> ReplicaId {
+        self.replica
+    }
+
+    pub fn moves(&self) -> &[LoggedMove<O>] {
+        &self.moves
+    }
+
+    /// Timestamp of the last logged move
+    pub fn latest(&self) -> Option<LamportTimestamp> {
+        self.moves.last().map(|logged| logged.timestamp)
+    }
+
+    /// Drop the logged moves up to `seen`, once every replica has applied every move up to it.
+    ///
+    /// Moves up to `seen` arriving afterwards can't be ordered before the dropped ones anymore,
+    /// so they are dropped with a warning.
+    pub fn trim(&mut self, seen: LamportTimestamp) {
+        let count = self
+            .moves
+            .iter()
+            .take_while(|logged| logged.timestamp <= seen)
+            .count();
+        trace!(?seen, count, "Trimming move log");
+        self.moves.drain(..count);
+        self.trimmed = self.trimmed.max(Some(seen));
+        // keep local timestamps after the trimmed moves
+        self.observe(seen);
+    }
+
+    /// The logged moves as [MoveCmd]s for other replicas, which place the targets exactly as they were placed here
+    pub fn commands(&self) -> Vec<MoveCmd<O>> {
+        self.moves
+            .iter()
+            .map(|logged| MoveCmd {
+                target: logged.target,
+                place: MoveToPlace::To(logged.child_of.clone()),
+                timestamp: Some(logged.timestamp),
+            })
+            .collect()
+    }
+
+    /// Timestamp for a new local move, after every move seen so far
+    pub(super) fn tick(&mut self) -> LamportTimestamp {
+        self.counter += 1;
+        LamportTimestamp {
+            counter: self.counter,
+            replica: self.replica,
+        }
+    }
+
+    /// Keep local timestamps after a move received from another replica
+    pub(super) fn observe(&mut self, timestamp: LamportTimestamp) {
+        self.counter = self.counter.max(timestamp.counter);
+    }
+}
+
+/// Apply a move in timestamp order: undo the later moves, do this one, then redo them
+pub(super) fn apply_move<O: SiblingOrder>(
+    log: &mut MoveLog<O>,
+    v_entities: &EntitiesView,
+    vm_child_of: &mut ViewMut<ChildOf<O>>,
+    vm_sibling_index: &mut ViewMut<SiblingIndex<O>>,
+    vm_parent_index: &mut ViewMut<ParentIndex<O>>,
+    (timestamp, target, child_of): (LamportTimestamp, EntityId, ChildOf<O>),
+) {
+    if matches!(log.trimmed, Some(trimmed) if timestamp <= trimmed) {
+        warn!(
+            ?timestamp,
+            entity = ?target,
+            ?child_of,
+            "Dropped a move ordered before the trimmed moves, trim only the moves every replica has seen"
+        );
+        return;
+    }
+
+    let position = match log
+        .moves
+        .binary_search_by_key(&timestamp, |logged| logged.timestamp)
+    {
+        Ok(position) => {
+            let logged = &log.moves[position];
+            if logged.target == target && logged.child_of == child_of {
+                debug!(?timestamp, "Move already applied");
+            } else {
+                // two replicas stamped moves with the same ReplicaId, so they will diverge
+                warn!(
+                    ?timestamp,
+                    entity = ?target,
+                    ?child_of,
+                    ?logged,
+                    "Dropped a move with the timestamp of another move, every replica needs its own ReplicaId"
+                );
+            }
+            return;
+        }
+        Err(position) => position,
+    };
+
+    let undone = log.moves.split_off(position);
+    if !undone.is_empty() {
+        trace!(count = undone.len(), "Undoing later moves");
+    }
+    for logged in undone.iter().rev() {
+        set_child_of(
+            v_entities,
+            vm_child_of,
+            vm_sibling_index,
+            vm_parent_index,
+            logged.target,
+            logged.previous.clone(),
+        );
+    }
+
+    let redone = undone
+        .into_iter()
+        .map(|logged| (logged.timestamp, logged.target, logged.child_of));
+    for (timestamp, target, child_of) in
+        std::iter::once((timestamp, target, child_of)).chain(redone)
+    {
+        let previous = {
+            let vm_child_of: &ViewMut<ChildOf<O>> = vm_child_of;
+            vm_child_of.get(target).ok().cloned()
+        };
+        let skipped = if !is_alive(v_entities, target, &child_of) {
+            debug!(?timestamp, entity = ?target, ?child_of, "Skipped move of or to an entity which isn't alive");
+            true
+        } else if is_ancestor_or_self(vm_child_of, target, child_of.0) {
+            debug!(?timestamp, entity = ?target, ?child_of, "Skipped move making an entity its own ancestor");
+            true
+        } else {
+            false
+        };
+        if !skipped {
+            set_child_of(
+                v_entities,
+                vm_child_of,
+                vm_sibling_index,
+                vm_parent_index,
+                target,
+                Some(child_of.clone()),
+            );
+        }
+        log.moves.push(LoggedMove {
+            timestamp,
+            target,
+            child_of,
+            previous,
+            skipped,
+        });
+    }
+}
+
+/// Whether `target` and its new parent are alive, unlinking moves have no parent
+fn is_alive<O: SiblingOrder>(
+    v_entities: &EntitiesView,
+    target: EntityId,
+    child_of: &ChildOf<O>,
+) -> bool {
+    v_entities.is_alive(target)
+        && (child_of.0 == EntityId::dead() || v_entities.is_alive(child_of.0))
+}
+
+/// Whether `entity` is `of` or one of its ancestors
+fn is_ancestor_or_self<O: SiblingOrder>(
+    vm_child_of: &ViewMut<ChildOf<O>>,
+    entity: EntityId,
+    of: EntityId,
+) -> bool {
+    let mut current = of;
+    for _ in 0..=vm_child_of.len() {
+        if current == entity {
+            return true;
+        }
+        current = match vm_child_of.get(current) {
+            Ok(ChildOf(parent, _)) => *parent,
+            Err(_) => return false,
+        };
+    }
+    false
+}
+
+fn set_child_of<O: SiblingOrder>(
+    v_entities: &EntitiesView,
+    vm_child_of: &mut ViewMut<ChildOf<O>>,
+    vm_sibling_index: &mut ViewMut<SiblingIndex<O>>,
+    vm_parent_index: &mut ViewMut<ParentIndex<O>>,
+    target: EntityId,
+    child_of: Option<ChildOf<O>>,
+) {
+    // moves are undone and redone after entities were deleted, only the entities still alive can be placed
+    if !v_entities.is_alive(target) {
+        return;
+    }
+    let child_of = child_of.filter(|child_of| is_alive(v_entities, target, child_of));
+
+    match child_of {
+        Some(child_of) if vm_child_of.contains(target) => {
+            *vm_child_of.get(target).unwrap() = child_of;
+        }
+        Some(child_of) => v_entities.add_component(target, &mut *vm_child_of, child_of),
+        None => {
+            vm_child_of.remove(target);
+        }
+    }
+
+    indexing::reindex_child(
+        v_entities,
+        vm_child_of,
+        vm_sibling_index,
+        vm_parent_index,
+        target,
+    );
+}
@@ -1,12 +1,74 @@
-#![allow(dead_code)]
 use shipyard::{EntityId, Component};
+use std::fmt::Debug;
+
+use super::FractionalIndex;
 
 /// ChildOf is the source of truth when it comes to the structure of things in trees.
 ///
 /// .0 is parent EntityId, .1 is Ordered relative to siblings
 #[derive(Clone, Debug, PartialEq, Eq, Component)]
 #[track(All)]
-pub struct ChildOf(pub EntityId, pub Ordered);
+pub struct ChildOf<O: SiblingOrder = Ordered>(pub EntityId, pub O);
+
+/// Key ordering a [ChildOf] among its siblings, either the fixed size [Ordered] or the growable [FractionalIndex]
+pub trait SiblingOrder: Clone + Debug + Ord + Send + Sync + 'static {
+    /// Key of the first child added to a parent
+    fn first() -> Self;
+    /// Key strictly between `min` and `max`, or `None` when there is no room left
+    fn checked_between(min: &Self, max: &Self) -> Option<Self>;
+    fn checked_after(&self) -> Option<Self>;
+    fn checked_before(&self) -> Option<Self>;
+    /// `count` ascending keys spread over the whole range, to renumber siblings which ran out of room
+    fn spread(count: usize) -> Vec<Self>;
+}
+
+impl SiblingOrder for Ordered {
+    fn first() -> Self {
+        Ordered::hinted(0)
+    }
+
+    fn checked_between(min: &Self, max: &Self) -> Option<Self> {
+        Ordered::checked_between(min, max)
+    }
+
+    fn checked_after(&self) -> Option<Self> {
+        Ordered::checked_after(self)
+    }
+
+    fn checked_before(&self) -> Option<Self> {
+        Ordered::checked_before(self)
+    }
+
+    fn spread(count: usize) -> Vec<Self> {
+        OrderedRange::full().evenly_spaced_between(count)
+    }
+}
+
+impl SiblingOrder for FractionalIndex {
+    fn first() -> Self {
+        FractionalIndex::default()
+    }
+
+    fn checked_between(min: &Self, max: &Self) -> Option<Self> {
+        if min < max {
+            Some(FractionalIndex::between(min, max))
+        } else {
+            None
+        }
+    }
+
+    fn checked_after(&self) -> Option<Self> {
+        Some(self.after())
+    }
+
+    fn checked_before(&self) -> Option<Self> {
+        Some(self.before())
+    }
+
+    fn spread(count: usize) -> Vec<Self> {
+        FractionalIndex::n_between(None, None, count)
+    }
+}
 
 impl ChildOf {
     pub fn new(child_of: EntityId, hint: u8) -> Self {
@@ -46,6 +108,21 @@ impl Ordered {
         const HALF_MIN: u32 = std::u32::MIN / 2;
         Ordered((self.0 / 2) + HALF_MIN)
     }
+
+    /// [Ordered::between], or `None` once there is no room left strictly between `min` and `max`
+    pub fn checked_between(min: &Self, max: &Self) -> Option<Self> {
+        Some(Ordered::between(min, max)).filter(|between| min < between && between < max)
+    }
+
+    /// [Ordered::after], or `None` once there is no room left after `self`
+    pub fn checked_after(&self) -> Option<Self> {
+        Some(self.after()).filter(|after| self < after)
+    }
+
+    /// [Ordered::before], or `None` once there is no room left before `self`
+    pub fn checked_before(&self) -> Option<Self> {
+        Some(self.before()).filter(|before| before < self)
+    }
 }
 
 #[test]
@@ -82,6 +159,33 @@ fn between_finds_the_floored_mid_point_between_two_ordereds() {
     assert_eq!(between, Ordered(5));
 }
 
+#[test]
+fn checked_between_runs_out_of_room_between_adjacent_ordereds() {
+    assert_eq!(
+        Ordered::checked_between(&Ordered(4), &Ordered(8)),
+        Some(Ordered(6))
+    );
+    assert_eq!(Ordered::checked_between(&Ordered(4), &Ordered(5)), None);
+    assert_eq!(Ordered::checked_between(&Ordered(4), &Ordered(4)), None);
+
+    // halving the gap towards the same spot runs out after ~32 moves
+    let min = Ordered::hinted(1);
+    let mut max = Ordered::hinted(2);
+    let mut moves = 0;
+    while let Some(between) = Ordered::checked_between(&min, &max) {
+        max = between;
+        moves += 1;
+    }
+    assert!(moves < 32);
+}
+
+#[test]
+fn checked_before_and_after_run_out_of_room_at_the_ends() {
+    assert_eq!(Ordered(10).checked_before(), Some(Ordered(5)));
+    assert_eq!(Ordered(0).checked_before(), None);
+    assert_eq!(Ordered(std::u32::MAX - 1).checked_after(), None);
+}
+
 #[test]
 fn after_divides_by_2_and_adds_half_of_u32max() {
     const HALF_MAX: u32 = 2147483647;
@@ -106,6 +210,11 @@ impl OrderedRange {
         OrderedRange(from.0, to.0)
     }
 
+    /// Every possible [Ordered] value
+    pub fn full() -> Self {
+        OrderedRange(std::u32::MIN, std::u32::MAX)
+    }
+
     /// Create a list of evenly spaced Ordered components within this OrderedRange.
     /// The start and end points are not guaranteed to be the same as the `from` and `to`.
     pub fn evenly_spaced_between(&self, length: usize) -> Vec<Ordered> {
@@ -0,0 +1,164 @@
+use super::*;
+use tracing::*;
+
+/// What happens to the children of a deleted entity, configured with [TreePlugin::with_orphan_policy].
+///
+/// Only applies when the parent entity itself is deleted, deleting just a parent's [ChildOf] keeps its children.
+/// The children's [ChildOf]s are changed without going through the [MoveLog], so replicas don't sync them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Component)]
+pub enum OrphanPolicy {
+    /// Delete the children and all of their descendants, once the current workload finishes
+    CascadeDelete,
+    /// Move the children to where the deleted entity was, under its parent (or detach them if it had none)
+    ReparentToGrandparent,
+    /// Remove the children's [ChildOf], making each of them the root of its own tree
+    DetachToRoot,
+}
+
+impl Default for OrphanPolicy {
+    fn default() -> Self {
+        OrphanPolicy::DetachToRoot
+    }
+}
+
+/// Apply `policy` to the children still pointing at the deleted parent
+pub(super) fn adopt_orphans<O: SiblingOrder>(
+    policy: OrphanPolicy,
+    commands: &Commands,
+    v_entities: &EntitiesView,
+    (vm_child_of, vm_sibling_index, vm_parent_index): (
+        &mut ViewMut<ChildOf<O>>,
+        &mut ViewMut<SiblingIndex<O>>,
+        &mut ViewMut<ParentIndex<O>>,
+    ),
+    deleted_child_of: &[(EntityId, ChildOf<O>)],
+    (parent_id, parent_index): (EntityId, ParentIndex<O>),
+) {
+    let orphans = {
+        let v_child_of: &ViewMut<ChildOf<O>> = vm_child_of;
+        parent_index
+            .children
+            .into_iter()
+            .map(|(_, child_id)| child_id)
+            .filter(|child_id| {
+                v_entities.is_alive(*child_id)
+                    && matches!(v_child_of.get(*child_id), Ok(ChildOf(parent, _)) if *parent == parent_id)
+            })
+            .collect::<Vec<_>>()
+    };
+    if orphans.is_empty() {
+        return;
+    }
+
+    let span = debug_span!("adopt_orphans", parent = ?parent_id, ?policy, count = orphans.len());
+    let _span = span.enter();
+
+    // the links between the orphans went with their parent's index
+    for orphan in &orphans {
+        vm_sibling_index.delete(*orphan);
+    }
+
+    let grandparent = match policy {
+        OrphanPolicy::CascadeDelete => {
+            for orphan in orphans {
+                for descendant in descendants(vm_parent_index, orphan) {
+                    commands.despawn(descendant);
+                }
+            }
+            return;
+        }
+        OrphanPolicy::ReparentToGrandparent => {
+            surviving_ancestor(v_entities, deleted_child_of, parent_id)
+        }
+        OrphanPolicy::DetachToRoot => None,
+    };
+
+    let ChildOf(grandparent_id, parent_key) = match grandparent {
+        Some(grandparent) => grandparent,
+        None => {
+            for orphan in orphans {
+                vm_child_of.remove(orphan);
+            }
+            return;
+        }
+    };
+
+    // take the deleted parent's place, before its next sibling
+    let next_key = vm_parent_index
+        .get(grandparent_id)
+        .ok()
+        .and_then(|grandparent_index| {
+            grandparent_index
+                .children
+                .iter()
+                .map(|(key, _)| key)
+                .find(|key| *key > &parent_key)
+                .cloned()
+        });
+    // only Ordered keys run out, FractionalIndex keys always have room before a greater key
+    let mut key = parent_key;
+    let mut exhausted = false;
+    for (i, orphan) in orphans.into_iter().enumerate() {
+        if i > 0 {
+            let between = match &next_key {
+                Some(next_key) => O::checked_between(&key, next_key),
+                None => key.checked_after(),
+            };
+            key = between.unwrap_or_else(|| {
+                exhausted = true;
+                key.clone()
+            });
+        }
+
+        *vm_child_of.get(orphan).unwrap() = ChildOf(grandparent_id, key.clone());
+        indexing::reindex_child(
+            v_entities,
+            vm_child_of,
+            vm_sibling_index,
+            vm_parent_index,
+            orphan,
+        );
+    }
+
+    if exhausted {
+        reordering::rebalance_children(
+            v_entities,
+            vm_child_of,
+            vm_sibling_index,
+            vm_parent_index,
+            grandparent_id,
+        );
+    }
+}
+
+/// Closest ancestor of `parent_id` which is still alive, as the [ChildOf] of the child leading to `parent_id`
+fn surviving_ancestor<O: SiblingOrder>(
+    v_entities: &EntitiesView,
+    deleted_child_of: &[(EntityId, ChildOf<O>)],
+    parent_id: EntityId,
+) -> Option<ChildOf<O>> {
+    let mut current = parent_id;
+    loop {
+        let (_, child_of) = deleted_child_of.iter().find(|(id, _)| *id == current)?;
+        if v_entities.is_alive(child_of.0) {
+            return Some(child_of.clone());
+        }
+        current = child_of.0;
+    }
+}
+
+/// `entity` and everything below it
+fn descendants<O: SiblingOrder>(
+    vm_parent_index: &ViewMut<ParentIndex<O>>,
+    entity: EntityId,
+) -> Vec<EntityId> {
+    let mut descendants = Vec::new();
+    let mut to_visit = vec![entity];
+    while let Some(entity) = to_visit.pop() {
+        if let Ok(parent_index) = vm_parent_index.get(entity) {
+            to_visit.extend(parent_index.children.iter().map(|(_, child)| *child));
+        }
+        descendants.push(entity);
+    }
+    descendants
+}
@@ -0,0 +1,203 @@
+use super::*;
+use tracing::*;
+
+#[derive(Clone, Debug)]
+pub struct MoveCmd<O: SiblingOrder = Ordered> {
+    pub target: EntityId,
+    pub place: MoveToPlace<O>,
+    /// Set on moves received from other replicas, local moves are stamped when applied, see [MoveLog]
+    pub timestamp: Option<LamportTimestamp>,
+}
+
+impl<O: SiblingOrder> MoveCmd<O> {
+    /// Local move
+    pub fn new(target: EntityId, place: MoveToPlace<O>) -> Self {
+        MoveCmd {
+            target,
+            place,
+            timestamp: None,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum MoveToPlace<O: SiblingOrder = Ordered> {
+    Unlink,
+    After(EntityId),
+    FirstChildOf(EntityId),
+    LastChildOf(EntityId),
+    /// Exactly this [ChildOf], as logged by another replica's [MoveLog::commands]
+    To(ChildOf<O>),
+}
+
+/// Applies the [MoveCmd]s queued in [MoveCommands] to the targets' [ChildOf], in order.
+///
+/// The [ParentIndex] and [SiblingIndex] are updated after each command, so every command sees the moves before it.
+/// When there is no key left between the target's new neighbours, all of the new parent's children are
+/// renumbered first, see [rebalance_children]. [FractionalIndex] keys always have room, so they are never renumbered.
+/// Moves after an entity which isn't a child (a root, unlinked by an earlier command, or with a rejected [ChildOf])
+/// are skipped with a warning.
+///
+/// Every move is recorded in the [MoveLog], and applied in timestamp order so replicas converge.
+pub fn tree_reordering<O: SiblingOrder>(
+    (v_entities, mut commands, mut log, mut vm_child_of, mut vm_parent_index, mut vm_sibling_index): (
+        EntitiesView,
+        UniqueViewMut<MoveCommands<O>>,
+        UniqueViewMut<MoveLog<O>>,
+        ViewMut<ChildOf<O>>,
+        ViewMut<ParentIndex<O>>,
+        ViewMut<SiblingIndex<O>>,
+    ),
+) {
+    // moves creating an ancestor loop are skipped by the move log
+    for cmd in commands.0.drain(..) {
+        let span = info_span!("applying move command", ?cmd);
+        let _entered = span.enter();
+        let child_of = match placement(&cmd, &vm_child_of, &vm_parent_index, &vm_sibling_index) {
+            Ok(child_of) => child_of,
+            Err(Unplaced::NotAChild(after)) => {
+                warn!(?after, "Skipped move after an entity which isn't a child");
+                continue;
+            }
+            Err(Unplaced::KeysExhausted(exhausted_parent)) => {
+                rebalance_children(
+                    &v_entities,
+                    &mut vm_child_of,
+                    &mut vm_sibling_index,
+                    &mut vm_parent_index,
+                    exhausted_parent,
+                );
+                placement(&cmd, &vm_child_of, &vm_parent_index, &vm_sibling_index)
+                    .expect("rebalanced children should have room between their keys")
+            }
+        };
+
+        let timestamp = match cmd.timestamp {
+            Some(timestamp) => {
+                log.observe(timestamp);
+                timestamp
+            }
+            None => log.tick(),
+        };
+
+        // also heals the indexes for the next command
+        move_log::apply_move(
+            &mut log,
+            &v_entities,
+            &mut vm_child_of,
+            &mut vm_sibling_index,
+            &mut vm_parent_index,
+            (timestamp, cmd.target, child_of),
+        );
+    }
+}
+
+/// Why [placement] found no [ChildOf] for a move
+#[derive(Debug)]
+enum Unplaced {
+    /// The children of this parent have no key left at that place
+    KeysExhausted(EntityId),
+    /// The entity to move after has no [ChildOf], or it isn't indexed
+    NotAChild(EntityId),
+}
+
+/// The [ChildOf] moving `cmd`'s target into place
+fn placement<O: SiblingOrder>(
+    cmd: &MoveCmd<O>,
+    vm_child_of: &ViewMut<ChildOf<O>>,
+    vm_parent_index: &ViewMut<ParentIndex<O>>,
+    vm_sibling_index: &ViewMut<SiblingIndex<O>>,
+) -> Result<ChildOf<O>, Unplaced> {
+    match cmd.place {
+        MoveToPlace::After(a) => {
+            // the target takes a's parent, between a and its current next sibling
+            let (ChildOf(a_of, a_ord), sibling) =
+                match (vm_child_of.get(a), vm_sibling_index.get(a)) {
+                    (Ok(child_of), Ok(sibling)) => (child_of.clone(), sibling),
+                    _ => return Err(Unplaced::NotAChild(a)),
+                };
+
+            let next_ord = match &sibling.next_sibling {
+                // siblings placed at the same spot by different replicas share a key, go after all of them
+                Some((next_ord, _)) if *next_ord == a_ord => vm_parent_index
+                    .get(a_of)
+                    .ok()
+                    .and_then(|parent_index: &ParentIndex<O>| {
+                        parent_index
+                            .children
+                            .iter()
+                            .map(|(key, _)| key)
+                            .find(|key| **key > a_ord)
+                    }),
+                Some((next_ord, _)) => Some(next_ord),
+                None => None,
+            };
+            let new_ord = match next_ord {
+                Some(next_ord) => O::checked_between(&a_ord, next_ord),
+                None => a_ord.checked_after(),
+            };
+
+            new_ord
+                .map(|ord| ChildOf(a_of, ord))
+                .ok_or(Unplaced::KeysExhausted(a_of))
+        }
+        MoveToPlace::FirstChildOf(parent) => match vm_parent_index
+            .get(parent)
+            .ok()
+            .and_then(|parent_index: &ParentIndex<O>| parent_index.children.first())
+        {
+            Some(first_child) => first_child
+                .0
+                .checked_before()
+                .map(|ord| ChildOf(parent, ord))
+                .ok_or(Unplaced::KeysExhausted(parent)),
+            // found no first child in index, create new ChildOf
+            None => Ok(ChildOf(parent, O::first())),
+        },
+        MoveToPlace::LastChildOf(parent) => match vm_parent_index
+            .get(parent)
+            .ok()
+            .and_then(|parent_index: &ParentIndex<O>| parent_index.children.last())
+        {
+            Some(last_child) => last_child
+                .0
+                .checked_after()
+                .map(|ord| ChildOf(parent, ord))
+                .ok_or(Unplaced::KeysExhausted(parent)),
+            // found no last child in index, create new ChildOf
+            None => Ok(ChildOf(parent, O::first())),
+        },
+        MoveToPlace::Unlink => Ok(ChildOf(EntityId::dead(), O::first())),
+        MoveToPlace::To(ref child_of) => Ok(child_of.clone()),
+    }
+}
+
+/// Renumber the children of `parent_id` evenly across the whole range of keys, keeping their order.
+///
+/// Updates their [ChildOf] and reindexes them right away.
+pub(super) fn rebalance_children<O: SiblingOrder>(
+    v_entities: &EntitiesView,
+    vm_child_of: &mut ViewMut<ChildOf<O>>,
+    vm_sibling_index: &mut ViewMut<SiblingIndex<O>>,
+    vm_parent_index: &mut ViewMut<ParentIndex<O>>,
+    parent_id: EntityId,
+) {
+    let children = match vm_parent_index.get(parent_id) {
+        Ok(parent_index) => parent_index.children.clone(),
+        Err(_) => return,
+    };
+    debug!(parent = ?parent_id, count = children.len(), "Sibling keys exhausted, rebalancing children");
+
+    let keys = O::spread(children.len());
+    for ((_, child_id), key) in children.into_iter().zip(keys) {
+        vm_child_of.get(child_id).unwrap().1 = key;
+        // the list stays sorted while mixing old and new keys, as both are in the same order
+        indexing::reindex_child(
+            v_entities,
+            vm_child_of,
+            vm_sibling_index,
+            vm_parent_index,
+            child_id,
+        );
+    }
+}
@@ -0,0 +1,81 @@
+use super::*;
+use tracing::*;
+
+/// Quarantined [ChildOf] which would have made its entity its own ancestor.
+///
+/// Added by the [tree_indexing] system in place of the offending [ChildOf], so walking up or down the tree always ends.
+#[derive(Clone, Debug, PartialEq, Component)]
+pub struct RejectedChildOf<O: SiblingOrder = Ordered>(pub ChildOf<O>);
+
+/// Event sent by the [tree_indexing] system whenever a [ChildOf] is rejected
+#[derive(Clone, Debug, PartialEq)]
+pub struct TreeViolation<O: SiblingOrder = Ordered> {
+    /// Entity whose [ChildOf] was moved to [RejectedChildOf]
+    pub entity: EntityId,
+    pub rejected: ChildOf<O>,
+    /// Ancestors walked from the rejected parent until `entity` was found again, `[entity]` when parented to itself
+    pub ancestors: Vec<EntityId>,
+}
+
+/// Ancestors of `child_id` up to and including itself, if it's one of them
+fn ancestor_loop<O: SiblingOrder>(
+    vm_child_of: &ViewMut<ChildOf<O>>,
+    child_id: EntityId,
+) -> Option<Vec<EntityId>> {
+    let mut ancestors = Vec::new();
+    let mut current = child_id;
+    loop {
+        current = vm_child_of.get(current).ok()?.0;
+        ancestors.push(current);
+        if current == child_id {
+            return Some(ancestors);
+        }
+        // only loops through child_id are rejected, so any other loop must already have been there
+        if ancestors.len() > vm_child_of.len() {
+            warn!(?ancestors, "Existing ancestor loop found in ChildOf");
+            return None;
+        }
+    }
+}
+
+/// Move every new or modified [ChildOf] which creates an ancestor loop to [RejectedChildOf], before it's indexed
+pub(super) fn reject_ancestor_loops<O: SiblingOrder>(
+    v_entities: &EntitiesView,
+    vm_child_of: &mut ViewMut<ChildOf<O>>,
+    vm_rejected_child_of: &mut ViewMut<RejectedChildOf<O>>,
+    vm_sibling_index: &mut ViewMut<SiblingIndex<O>>,
+    vm_parent_index: &mut ViewMut<ParentIndex<O>>,
+    violations: &mut EventWriter<TreeViolation<O>>,
+) {
+    let changed = vm_child_of
+        .inserted_or_modified()
+        .iter()
+        .ids()
+        .collect::<Vec<_>>();
+
+    // checked one at a time, so of two ChildOf pointing at each other only the first is rejected
+    for child_id in changed {
+        let ancestors = match ancestor_loop(vm_child_of, child_id) {
+            Some(ancestors) => ancestors,
+            None => continue,
+        };
+
+        // a modified ChildOf may still be indexed under its previous parent
+        if vm_sibling_index.contains(child_id) {
+            indexing::unlink_child(vm_sibling_index, vm_parent_index, child_id);
+        }
+
+        let rejected = vm_child_of.remove(child_id).unwrap();
+        warn!(entity = ?child_id, ?rejected, ?ancestors, "Rejected ChildOf creating an ancestor loop");
+        v_entities.add_component(
+            child_id,
+            &mut *vm_rejected_child_of,
+            RejectedChildOf(rejected.clone()),
+        );
+        violations.send(TreeViolation {
+            entity: child_id,
+            rejected,
+            ancestors,
+        });
+    }
+}
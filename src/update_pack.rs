@@ -0,0 +1,12 @@
+//! Reset the tracking of update packed storages at the end of every update.
+use tracing::trace_span;
+
+use crate::prelude::*;
+
+use core::any::type_name;
+
+pub(crate) fn reset_update_pack<T: Component<Tracking = track::All>>(mut vm_t: ViewMut<T>) {
+    let span = trace_span!("reset_update_pack", update_packed = ?type_name::<T>());
+    let _span = span.enter();
+    vm_t.clear_all_inserted_and_modified();
+}